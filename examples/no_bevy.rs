@@ -1,7 +1,6 @@
 // In this program, we generate paths through a tilemap
 
 use glam::{UVec2, Vec2};
-use navmesh::NavVec3;
 use seldom_map_nav::{mesh::generate_navmesh, prelude::*};
 
 // For simplicity, let's assume 0, 0 is at the top left
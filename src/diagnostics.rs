@@ -0,0 +1,40 @@
+//! Diagnostics for tuning the cost of navigation
+
+use bevy::diagnostic::{Diagnostic, DiagnosticId, RegisterDiagnostic};
+
+use crate::prelude::*;
+
+pub(crate) fn diagnostics_plugin(app: &mut App) {
+    app.register_diagnostic(Diagnostic::new(
+        MapNavDiagnostics::PATHFINDERS,
+        "pathfinders",
+        1,
+    ))
+    .register_diagnostic(Diagnostic::new(
+        MapNavDiagnostics::REPATHS,
+        "repaths_per_second",
+        20,
+    ))
+    .register_diagnostic(Diagnostic::new(
+        MapNavDiagnostics::PATH_LENGTH,
+        "average_path_length",
+        20,
+    ));
+}
+
+/// Diagnostics that this crate measures when the `diagnostics` feature is enabled.
+/// They show up in `LogDiagnosticsPlugin`'s output.
+#[derive(Debug)]
+pub struct MapNavDiagnostics;
+
+impl MapNavDiagnostics {
+    /// Number of entities with a [`Pathfind`]
+    pub const PATHFINDERS: DiagnosticId =
+        DiagnosticId::from_u128(95953027944727263751811757113739037618);
+    /// Number of paths generated per second
+    pub const REPATHS: DiagnosticId =
+        DiagnosticId::from_u128(208754517545122628165233908897870384110);
+    /// Average length, in world units, of the paths generated in a frame
+    pub const PATH_LENGTH: DiagnosticId =
+        DiagnosticId::from_u128(24716307588224165950553723884962782173);
+}
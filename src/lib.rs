@@ -3,6 +3,8 @@
 
 #![warn(missing_docs)]
 
+#[cfg(feature = "diagnostics")]
+pub mod diagnostics;
 pub mod mesh;
 #[cfg(feature = "bevy")]
 mod nav;
@@ -10,22 +12,32 @@ mod nav;
 mod plugin;
 #[cfg(feature = "bevy")]
 pub mod set;
+#[cfg(feature = "bevy")]
+mod task;
 mod vertex;
 
 /// Module for convenient imports. Use with `use seldom_map_nav::prelude::*;`.
 pub mod prelude {
     #[cfg(feature = "bevy")]
     pub(crate) use bevy::prelude::*;
-    #[cfg(feature = "bevy")]
-    pub(crate) use seldom_interop::prelude::*;
     #[cfg(feature = "state")]
     pub(crate) use seldom_state::prelude::*;
 
-    pub use crate::mesh::{Navability, Navmeshes};
+    pub use crate::mesh::{Cardinal, Navability, Navmeshes, DEFAULT_PATH_MODE, DEFAULT_QUERY};
+    #[cfg(all(feature = "bevy", feature = "serde"))]
+    pub use crate::nav::PathfindSnapshot;
     #[cfg(feature = "bevy")]
     pub use crate::{
-        nav::{Nav, NavBundle, PathTarget, Pathfind},
+        nav::{
+            FaceTarget, Facing, Nav, NavArrived, NavBlocked, NavBundle, NavEnabled, NavFailReason,
+            NavFailed, NavGroup, NavGroupArrived, NavHistory, NavMotionState, NavOutput, NavPaths,
+            NavStarted, NavTimeOverride, NavTrail, NavigateManyExt, PathCache, PathPostProcessor,
+            PathStatus, PathTarget, Pathfind, SharedPath, TargetQueue, VelocityOverride,
+        },
         plugin::{map_nav_plugin, MapNavPlugin},
+        task::{NavmeshReady, NavmeshTask},
     };
-    pub use navmesh::{NavPathMode, NavQuery};
+    pub use navmesh::{NavMesh, NavPathMode, NavQuery, NavVec3};
+    #[cfg(feature = "bevy")]
+    pub use seldom_interop::prelude::Position2;
 }
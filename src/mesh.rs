@@ -2,77 +2,629 @@
 
 #[cfg(feature = "bevy")]
 use crate::prelude::*;
+pub use crate::vertex::Cardinal;
 use crate::vertex::{Ordinal, VertexNormal};
 
 #[cfg(feature = "bevy")]
-use bevy::utils::HashMap;
+use bevy::utils::{HashMap, HashSet};
 use cdt::triangulate_with_edges;
-use glam::{UVec2, Vec2};
+use glam::{UVec2, Vec2, Vec3};
 use mint::Vector3;
-use navmesh::NavMesh;
+use navmesh::{NavMesh, NavPathMode, NavQuery, NavVec3, Scalar};
+#[cfg(feature = "rand")]
+use rand::Rng;
 #[cfg(not(feature = "bevy"))]
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::{
     error::Error,
     fmt::{self, Debug, Display, Formatter},
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::OnceLock,
+    time::{Duration, Instant},
 };
 
+/// Quality of querying a point on the navmesh to use when you don't need to trade accuracy
+/// for speed
+pub const DEFAULT_QUERY: NavQuery = NavQuery::Accuracy;
+/// Quality of finding a path to use when you don't need to trade accuracy for speed
+pub const DEFAULT_PATH_MODE: NavPathMode = NavPathMode::Accuracy;
+
 #[derive(Clone, Debug)]
 struct NavmeshEntry {
     navmesh: NavMesh,
     clearance: f32,
+    // Pairs of triangles that cannot be crossed from the first to the second
+    blocked_transitions: HashSet<(usize, usize)>,
+    // For each triangle, the triangle across each of its edges, if any. Edge `i` goes
+    // from the triangle's `i`th vertex to the next.
+    neighbors: Vec<[Option<usize>; 3]>,
+}
+
+// A clearance's navmesh, which is generated when first needed if `Navmeshes` is lazy.
+// Holds `None` if generation failed.
+#[derive(Clone, Debug)]
+struct NavmeshSlot {
+    clearance: f32,
+    entry: OnceLock<Option<NavmeshEntry>>,
+}
+
+impl NavmeshSlot {
+    fn generated(entry: NavmeshEntry) -> Self {
+        Self {
+            clearance: entry.clearance,
+            entry: OnceLock::from(Some(entry)),
+        }
+    }
 }
 
 /// Put this component on your tilemap. Stores your map's navmeshes.
 #[cfg_attr(feature = "bevy", derive(Component))]
 #[derive(Clone, Debug)]
-pub struct Navmeshes(Vec<NavmeshEntry>);
+pub struct Navmeshes {
+    navmeshes: Vec<NavmeshSlot>,
+    map_size: UVec2,
+    tile_size: Vec2,
+    pub(crate) navability: Vec<Navability>,
+}
 
 impl Navmeshes {
-    /// Generate navmeshes for your tilemap. The input to `navability` is a tile's position.
-    /// `clearances` will be sorted for you.
+    /// Generate navmeshes for your tilemap. The input to `navability` is a tile's position. Tiles
+    /// may be rectangular. `clearances` will be sorted for you, and clearances within 0.001 of each
+    /// other are merged. Every navmesh is generated before this returns. To keep navigators away
+    /// from the edge of the map, wrap `navability` with [`bordered`].
     pub fn generate(
         map_size: UVec2,
         tile_size: Vec2,
         navability: impl Fn(UVec2) -> Navability,
         clearances: impl IntoIterator<Item = f32>,
     ) -> Result<Self, NavmeshGenError> {
-        let mut clearances = clearances.into_iter().collect::<Vec<_>>();
-        clearances.sort_by(f32::total_cmp);
+        Self::generate_with_stats(map_size, tile_size, navability, clearances)
+            .map(|(navmeshes, _)| navmeshes)
+    }
+
+    /// Like [`Navmeshes::generate`], but also reports how long each clearance's navmesh took
+    /// to generate and how large it is, such as to decide how many clearances are affordable
+    /// at load time
+    pub fn generate_with_stats(
+        map_size: UVec2,
+        tile_size: Vec2,
+        navability: impl Fn(UVec2) -> Navability,
+        clearances: impl IntoIterator<Item = f32>,
+    ) -> Result<(Self, GenStats), NavmeshGenError> {
+        Self::generate_from_grid(
+            map_size,
+            tile_size,
+            navability_grid(map_size, navability),
+            clearances,
+        )
+    }
+
+    /// Like [`Navmeshes::generate`], but generates each clearance's navmesh the first time it's
+    /// needed, which stalls that query. Queries for a clearance whose navmesh fails to generate
+    /// return `None`.
+    pub fn generate_lazy(
+        map_size: UVec2,
+        tile_size: Vec2,
+        navability: impl Fn(UVec2) -> Navability,
+        clearances: impl IntoIterator<Item = f32>,
+    ) -> Self {
+        let (clearances, _) = sorted_clearances(clearances);
+
+        Self {
+            navmeshes: clearances
+                .into_iter()
+                .map(|clearance| NavmeshSlot {
+                    clearance,
+                    entry: OnceLock::new(),
+                })
+                .collect(),
+            map_size,
+            tile_size,
+            navability: navability_grid(map_size, navability),
+        }
+    }
+
+    /// Like [`Navmeshes::generate`], but takes the navability of every tile as a slice,
+    /// such as for maps loaded from data. The tile at `(x, y)` is at index `y * map_size.x + x`.
+    /// Returns [`NavmeshGenError::GridSize`] if the slice doesn't have one tile per position.
+    pub fn from_grid(
+        map_size: UVec2,
+        tile_size: Vec2,
+        navability: &[Navability],
+        clearances: impl IntoIterator<Item = f32>,
+    ) -> Result<Self, NavmeshGenError> {
+        let expected = (map_size.x * map_size.y) as usize;
+        if navability.len() != expected {
+            return Err(NavmeshGenError::GridSize {
+                expected,
+                actual: navability.len(),
+            });
+        }
+
+        Self::generate_from_grid(map_size, tile_size, navability.to_vec(), clearances)
+            .map(|(navmeshes, _)| navmeshes)
+    }
+
+    fn generate_from_grid(
+        map_size: UVec2,
+        tile_size: Vec2,
+        navability: Vec<Navability>,
+        clearances: impl IntoIterator<Item = f32>,
+    ) -> Result<(Self, GenStats), NavmeshGenError> {
+        let (clearances, merged) = sorted_clearances(clearances);
 
         let mut navmeshes = Vec::with_capacity(clearances.len());
+        let mut stats = GenStats {
+            merged,
+            ..Default::default()
+        };
         for clearance in clearances {
-            navmeshes.push(NavmeshEntry {
-                navmesh: generate_navmesh(map_size, tile_size, &navability, clearance)?,
+            let start = Instant::now();
+            let entry = generate_navmesh_from_grid(map_size, tile_size, &navability, clearance)?;
+            stats.meshes.push(MeshGenStats {
                 clearance,
+                duration: start.elapsed(),
+                triangles: entry.navmesh.triangles().len(),
+                vertices: entry.navmesh.vertices().len(),
             });
+            navmeshes.push(NavmeshSlot::generated(entry));
         }
 
-        Ok(Self(navmeshes))
+        Ok((
+            Self {
+                navmeshes,
+                map_size,
+                tile_size,
+                navability,
+            },
+            stats,
+        ))
     }
 
     /// Gets the navmesh with the least amount of clearance
     /// greater than or equal to the given clearance
     pub fn mesh(&self, clearance: f32) -> Option<&NavMesh> {
-        let Navmeshes(navmeshes) = self;
-        navmeshes
-            .get(navmeshes.partition_point(|navmesh| clearance > navmesh.clearance))
-            .map(|navmesh| &navmesh.navmesh)
+        self.entry(clearance).map(|entry| &entry.navmesh)
+    }
+
+    fn entry(&self, clearance: f32) -> Option<&NavmeshEntry> {
+        let navmeshes = &self.navmeshes;
+        self.slot_entry(
+            navmeshes.get(navmeshes.partition_point(|navmesh| clearance > navmesh.clearance))?,
+        )
+    }
+
+    fn slot_entry<'a>(&'a self, slot: &'a NavmeshSlot) -> Option<&'a NavmeshEntry> {
+        slot.entry
+            .get_or_init(|| {
+                generate_navmesh_from_grid(
+                    self.map_size,
+                    self.tile_size,
+                    &self.navability,
+                    slot.clearance,
+                )
+                .ok()
+            })
+            .as_ref()
+    }
+
+    /// Finds a path from `from` to `to` on the navmesh given by [`Navmeshes::mesh`]. Unlike
+    /// pathfinding on the [`NavMesh`] directly, this respects [`Navability::OneWay`] tiles.
+    pub fn find_path(
+        &self,
+        clearance: f32,
+        from: Vec2,
+        to: Vec2,
+        query: NavQuery,
+        path_mode: NavPathMode,
+    ) -> Option<Vec<Vec2>> {
+        Some(
+            self.find_nav_path(clearance, from, to, query, path_mode)?
+                .into_iter()
+                .map(|pos| Vec3::from(Vector3::from(pos)).truncate())
+                .collect(),
+        )
+    }
+
+    /// Gets the length of the path that [`Navmeshes::find_path`] would find with the most
+    /// accurate settings, such as to compare travel distances to several objectives. This skips
+    /// converting the waypoints, but the search still builds them internally.
+    pub fn path_cost(&self, clearance: f32, from: Vec2, to: Vec2) -> Option<f32> {
+        Some(NavMesh::path_length(&self.find_nav_path(
+            clearance,
+            from,
+            to,
+            NavQuery::Accuracy,
+            NavPathMode::Accuracy,
+        )?))
+    }
+
+    fn find_nav_path(
+        &self,
+        clearance: f32,
+        from: Vec2,
+        to: Vec2,
+        query: NavQuery,
+        path_mode: NavPathMode,
+    ) -> Option<Vec<NavVec3>> {
+        let entry = self.entry(clearance)?;
+        let from = Vector3::from(from.extend(0.)).into();
+        let to = Vector3::from(to.extend(0.)).into();
+        let filter = |_, from, to| !entry.blocked_transitions.contains(&(from, to));
+
+        // `navmesh` gives filtered connections the maximum cost instead of removing them,
+        // so check that the cheapest path doesn't take one
+        if !entry.blocked_transitions.is_empty() {
+            let (_, cost) = entry.navmesh.find_path_triangles_custom(
+                entry.navmesh.find_closest_triangle(from, query)?,
+                entry.navmesh.find_closest_triangle(to, query)?,
+                filter,
+            )?;
+
+            if cost >= Scalar::MAX {
+                return None;
+            }
+        }
+
+        entry
+            .navmesh
+            .find_path_custom(from, to, query, path_mode, filter)
+    }
+
+    /// Finds the path that a `Pathfind` at `from` would be given toward `to`, to draw it before
+    /// it's followed, for example. Returns an empty path if none is found.
+    pub fn preview_path(&self, clearance: f32, from: Vec2, to: Vec2) -> Vec<Vec2> {
+        let from = self.nearest_point(clearance, from).unwrap_or(from);
+        self.find_path(clearance, from, to, DEFAULT_QUERY, DEFAULT_PATH_MODE)
+            .unwrap_or_default()
+    }
+
+    /// Gets the point closest to `point` on the navmesh given by [`Navmeshes::mesh`]
+    pub fn nearest_point(&self, clearance: f32, point: Vec2) -> Option<Vec2> {
+        let point = self
+            .mesh(clearance)?
+            .closest_point(Vector3::from(point.extend(0.)).into(), NavQuery::Accuracy)?;
+        Some(Vec3::from(Vector3::from(point)).truncate())
+    }
+
+    /// Checks whether `point` is on the navmesh given by [`Navmeshes::mesh`], so a navigator
+    /// with the given clearance can stand there
+    pub fn is_navigable(&self, clearance: f32, point: Vec2) -> bool {
+        self.nearest_point(clearance, point)
+            .is_some_and(|nearest| nearest.distance(point) <= ON_MESH_TOLERANCE)
+    }
+
+    /// Gets the indices of the `points` that aren't navigable by [`Navmeshes::is_navigable`],
+    /// such as to catch misplaced spawn points when loading a level
+    pub fn validate_points(&self, clearance: f32, points: &[Vec2]) -> Vec<usize> {
+        points
+            .iter()
+            .enumerate()
+            .filter(|&(_, &point)| !self.is_navigable(clearance, point))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Picks a uniformly random point on the navmesh given by [`Navmeshes::mesh`], such as
+    /// for wandering or spawning. The point is navigable for navigators with the given
+    /// clearance, but may not be reachable from every other point on the map.
+    #[cfg(feature = "rand")]
+    pub fn random_point(&self, clearance: f32, rng: &mut impl Rng) -> Option<Vec2> {
+        let navmesh = self.mesh(clearance)?;
+        random_point_in(navmesh, &navmesh.areas().iter().collect::<Vec<_>>(), rng)
+    }
+
+    /// Like [`Navmeshes::random_point`], but picks a point within `max_dist` of `origin`.
+    /// Returns `None` if no navigable point is found there.
+    #[cfg(feature = "rand")]
+    pub fn random_point_near(
+        &self,
+        clearance: f32,
+        origin: Vec2,
+        max_dist: f32,
+        rng: &mut impl Rng,
+    ) -> Option<Vec2> {
+        (0..RANDOM_POINT_ATTEMPTS).find_map(|_| {
+            let angle = rng.gen_range(0. ..std::f32::consts::TAU);
+            let point = origin + Vec2::from_angle(angle) * max_dist * rng.gen::<f32>().sqrt();
+            self.is_navigable(clearance, point).then_some(point)
+        })
+    }
+
+    /// Checks whether a navigator with the given clearance can move in a straight line from `from`
+    /// to `to` on the navmesh, respecting [`Navability::OneWay`] tiles
+    pub fn line_of_sight(&self, clearance: f32, from: Vec2, to: Vec2) -> bool {
+        let Some(entry) = self.entry(clearance) else { return false };
+        let navmesh = &entry.navmesh;
+        let from = Vector3::from(from.extend(0.)).into();
+        let Some(mut triangle) = navmesh.find_closest_triangle(from, NavQuery::Accuracy) else {
+            return false;
+        };
+        let Some(from) = navmesh.closest_point(from, NavQuery::Accuracy) else { return false };
+        let from = Vec3::from(Vector3::from(from)).truncate();
+
+        let mut previous = None;
+        for _ in 0..navmesh.triangles().len() {
+            let [v1, v2, v3] = triangle_corners(navmesh, triangle);
+            let winding = (v2 - v1).perp_dot(v3 - v1).signum();
+
+            // The edge that the line leaves the triangle through, if it doesn't end inside it
+            let exit = [(v1, v2), (v2, v3), (v3, v1)]
+                .into_iter()
+                .zip(entry.neighbors[triangle])
+                .find(|&((start, end), neighbor)| {
+                    (neighbor.is_none() || neighbor != previous)
+                        && (end - start).perp_dot(to - start) * winding < 0.
+                        && (to - from).perp_dot(start - from) * (to - from).perp_dot(end - from)
+                            <= 0.
+                });
+
+            let Some((_, neighbor)) = exit else { return true };
+            let Some(neighbor) = neighbor else { return false };
+            if entry.blocked_transitions.contains(&(triangle, neighbor)) {
+                return false;
+            }
+
+            previous = Some(triangle);
+            triangle = neighbor;
+        }
+
+        false
+    }
+
+    /// Gets the closest point to `point` on the edge of the navmesh given by [`Navmeshes::mesh`],
+    /// including the edges around obstacles
+    pub fn nearest_boundary(&self, clearance: f32, point: Vec2) -> Option<Vec2> {
+        let entry = self.entry(clearance)?;
+        entry
+            .neighbors
+            .iter()
+            .enumerate()
+            .flat_map(|(triangle, neighbors)| {
+                let [v1, v2, v3] = triangle_corners(&entry.navmesh, triangle);
+                [(v1, v2), (v2, v3), (v3, v1)]
+                    .into_iter()
+                    .zip(*neighbors)
+                    .filter(|(_, neighbor)| neighbor.is_none())
+                    .map(|((start, end), _)| {
+                        let edge = end - start;
+                        let along =
+                            ((point - start).dot(edge) / edge.length_squared()).clamp(0., 1.);
+                        start + edge * along
+                    })
+            })
+            .min_by(|a, b| {
+                a.distance_squared(point)
+                    .total_cmp(&b.distance_squared(point))
+            })
+    }
+
+    /// Gets the closest point to `to` on the part of the navmesh that a navigator at `from` can
+    /// reach, respecting [`Navability::OneWay`] tiles
+    pub fn nearest_reachable_point(&self, clearance: f32, from: Vec2, to: Vec2) -> Option<Vec2> {
+        let entry = self.entry(clearance)?;
+        let navmesh = &entry.navmesh;
+        let start = navmesh
+            .find_closest_triangle(Vector3::from(from.extend(0.)).into(), NavQuery::Accuracy)?;
+
+        let mut reached = HashSet::<usize>::default();
+        reached.insert(start);
+        let mut frontier = vec![start];
+        while let Some(triangle) = frontier.pop() {
+            for neighbor in entry.neighbors[triangle].into_iter().flatten() {
+                if !entry.blocked_transitions.contains(&(triangle, neighbor))
+                    && reached.insert(neighbor)
+                {
+                    frontier.push(neighbor);
+                }
+            }
+        }
+
+        reached
+            .into_iter()
+            .map(|triangle| closest_point_in_triangle(triangle_corners(navmesh, triangle), to))
+            .min_by(|a, b| a.distance_squared(to).total_cmp(&b.distance_squared(to)))
     }
 
     /// Gets a navmesh at the given index. Navmeshes are sorted from least to most clearance.
     pub fn mesh_at(&self, mesh: usize) -> Option<&NavMesh> {
-        let Navmeshes(navmeshes) = self;
-        navmeshes.get(mesh).map(|entry| &entry.navmesh)
+        self.slot_entry(self.navmeshes.get(mesh)?)
+            .map(|entry| &entry.navmesh)
     }
 
     /// Gets the number of navmeshes
     pub fn mesh_count(&self) -> usize {
-        let Navmeshes(navmeshes) = self;
-        navmeshes.len()
+        self.navmeshes.len()
+    }
+
+    /// Writes the navmesh given by [`Navmeshes::mesh`] to `path` as a Wavefront OBJ file,
+    /// such as to inspect its triangulation in a 3D modeling tool. The mesh lies on the XY plane.
+    pub fn export_obj(&self, clearance: f32, path: impl AsRef<Path>) -> io::Result<()> {
+        let navmesh = self.mesh(clearance).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("missing navmesh with clearance of at least {clearance}"),
+            )
+        })?;
+        let mut file = BufWriter::new(File::create(path)?);
+
+        for vertex in navmesh.vertices() {
+            writeln!(file, "v {} {} {}", vertex.x, vertex.y, vertex.z)?;
+        }
+        // OBJ indices start at 1
+        for triangle in navmesh.triangles() {
+            writeln!(
+                file,
+                "f {} {} {}",
+                triangle.first + 1,
+                triangle.second + 1,
+                triangle.third + 1
+            )?;
+        }
+
+        file.flush()
+    }
+
+    /// Sets the navability of every tile whose center is within `radius` of `center`,
+    /// and regenerates the navmeshes. With the `bevy` feature, navigators on this map repath
+    /// in response. This regenerates every clearance's navmesh, which costs as much
+    /// as the original generation, so avoid calling it every frame. If generation fails,
+    /// the navmeshes are left unchanged.
+    pub fn set_region_navability(
+        &mut self,
+        center: Vec2,
+        radius: f32,
+        navable: bool,
+    ) -> Result<(), NavmeshGenError> {
+        *self = self.regeneration(self.region_navability(center, radius, navable))()?;
+        Ok(())
+    }
+
+    // Gets the navability of every tile, with every tile whose center is within `radius`
+    // of `center` set
+    pub(crate) fn region_navability(
+        &self,
+        center: Vec2,
+        radius: f32,
+        navable: bool,
+    ) -> Vec<Navability> {
+        let mut navability = self.navability.clone();
+        // Tiles set here lose any one-way direction they had
+        let tile_navability = match navable {
+            true => Navability::Navable,
+            false => Navability::Solid,
+        };
+
+        for y in 0..self.map_size.y {
+            for x in 0..self.map_size.x {
+                let tile_center = (UVec2::new(x, y).as_vec2() + 0.5) * self.tile_size;
+                if tile_center.distance(center) <= radius {
+                    navability[(y * self.map_size.x + x) as usize] = tile_navability;
+                }
+            }
+        }
+
+        navability
+    }
+
+    // Gets a function that generates these navmeshes with the given navability, without borrowing
+    // `self`, so it can run in the background
+    pub(crate) fn regeneration(
+        &self,
+        navability: Vec<Navability>,
+    ) -> impl FnOnce() -> Result<Self, NavmeshGenError> + Send + 'static {
+        // Clearances, and whether their navmeshes have been generated
+        let slots = self
+            .navmeshes
+            .iter()
+            .map(|slot| (slot.clearance, slot.entry.get().is_some()))
+            .collect::<Vec<_>>();
+        let (map_size, tile_size) = (self.map_size, self.tile_size);
+
+        move || {
+            let mut navmeshes = Vec::with_capacity(slots.len());
+            for (clearance, generated) in slots {
+                // Lazy navmeshes that haven't been needed yet are generated from the new tiles
+                // when they are
+                navmeshes.push(match generated {
+                    true => NavmeshSlot::generated(generate_navmesh_from_grid(
+                        map_size,
+                        tile_size,
+                        &navability,
+                        clearance,
+                    )?),
+                    false => NavmeshSlot {
+                        clearance,
+                        entry: OnceLock::new(),
+                    },
+                });
+            }
+
+            Ok(Self {
+                navmeshes,
+                map_size,
+                tile_size,
+                navability,
+            })
+        }
     }
 }
 
+fn triangle_corners(navmesh: &NavMesh, triangle: usize) -> [Vec2; 3] {
+    let triangle = &navmesh.triangles()[triangle];
+    [triangle.first, triangle.second, triangle.third].map(|vertex| {
+        let vertex = navmesh.vertices()[vertex as usize];
+        Vec2::new(vertex.x, vertex.y)
+    })
+}
+
+// Gets the closest point to `point` in the triangle with the given corners
+fn closest_point_in_triangle([v1, v2, v3]: [Vec2; 3], point: Vec2) -> Vec2 {
+    let winding = (v2 - v1).perp_dot(v3 - v1).signum();
+    let edges = [(v1, v2), (v2, v3), (v3, v1)];
+    if edges
+        .iter()
+        .all(|&(start, end)| (end - start).perp_dot(point - start) * winding >= 0.)
+    {
+        return point;
+    }
+
+    edges
+        .into_iter()
+        .map(|(start, end)| {
+            let edge = end - start;
+            let along = match edge.length_squared() > 0. {
+                true => ((point - start).dot(edge) / edge.length_squared()).clamp(0., 1.),
+                false => 0.,
+            };
+            start + edge * along
+        })
+        .min_by(|a, b| {
+            a.distance_squared(point)
+                .total_cmp(&b.distance_squared(point))
+        })
+        .unwrap()
+}
+
+// How many points `Navmeshes::random_point_near` tries before giving up
+#[cfg(feature = "rand")]
+const RANDOM_POINT_ATTEMPTS: u32 = 32;
+// Distance from the navmesh within which a point is considered on it
+pub(crate) const ON_MESH_TOLERANCE: f32 = 0.001;
+
+// Picks a uniformly random point within the given areas of the navmesh
+#[cfg(feature = "rand")]
+fn random_point_in(
+    navmesh: &NavMesh,
+    areas: &[&navmesh::NavArea],
+    rng: &mut impl Rng,
+) -> Option<Vec2> {
+    let total = areas.iter().map(|area| area.size).sum::<f32>();
+    if total <= 0. {
+        return None;
+    }
+
+    let mut pick = rng.gen_range(0. ..total);
+    let area = areas
+        .iter()
+        .find(|area| {
+            pick -= area.size;
+            pick < 0.
+        })
+        .or(areas.last())?;
+
+    let [v1, v2, v3] = triangle_corners(navmesh, area.triangle as usize);
+
+    let (mut a, mut b) = (rng.gen::<f32>(), rng.gen::<f32>());
+    if a + b > 1. {
+        (a, b) = (1. - a, 1. - b);
+    }
+
+    Some(v1 + (v2 - v1) * a + (v3 - v1) * b)
+}
+
 /// Represents the conditions under which this tile is navigable. More variants
 /// should be added in the future, as breaking changes.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -81,6 +633,56 @@ pub enum Navability {
     Navable,
     /// This tile cannot be navigated and navigators should avoid colliding with it
     Solid,
+    /// This tile can be navigated, but navigators cannot enter or leave it moving against the given
+    /// direction, like a ledge that can be dropped off but not climbed
+    OneWay(Cardinal),
+}
+
+/// Wrap a `navability` function so that tiles within `border` tiles of the edge of the map
+/// are [`Navability::Solid`], guaranteeing a margin of impassable tiles around the navmesh
+pub fn bordered(
+    map_size: UVec2,
+    border: u32,
+    navability: impl Fn(UVec2) -> Navability,
+) -> impl Fn(UVec2) -> Navability {
+    move |pos| match pos.x < border
+        || pos.y < border
+        || pos.x + border >= map_size.x
+        || pos.y + border >= map_size.y
+    {
+        true => Navability::Solid,
+        false => navability(pos),
+    }
+}
+
+/// Statistics about generating [`Navmeshes`], from [`Navmeshes::generate_with_stats`]
+#[derive(Clone, Debug, Default)]
+pub struct GenStats {
+    /// Statistics for each clearance's navmesh, from least to most clearance
+    pub meshes: Vec<MeshGenStats>,
+    /// Clearances that were dropped for being within 0.001 of a larger clearance, which was
+    /// generated instead. Usually a sign of a typo in the map's configuration.
+    pub merged: Vec<f32>,
+}
+
+impl GenStats {
+    /// Gets the total time spent generating navmeshes
+    pub fn duration(&self) -> Duration {
+        self.meshes.iter().map(|mesh| mesh.duration).sum()
+    }
+}
+
+/// Statistics about generating one clearance's navmesh
+#[derive(Clone, Copy, Debug)]
+pub struct MeshGenStats {
+    /// Clearance that the navmesh was generated with
+    pub clearance: f32,
+    /// Time spent generating the navmesh
+    pub duration: Duration,
+    /// Number of triangles in the navmesh
+    pub triangles: usize,
+    /// Number of vertices in the navmesh
+    pub vertices: usize,
 }
 
 /// Error that can emit when generating a navmesh
@@ -90,6 +692,13 @@ pub enum NavmeshGenError {
     Triangulation(cdt::Error),
     /// Error related to constructing the navmesh from triangles
     Navmesh(navmesh::Error),
+    /// The navability grid doesn't have one tile for every position on the map
+    GridSize {
+        /// Number of tiles on the map
+        expected: usize,
+        /// Number of tiles in the grid
+        actual: usize,
+    },
 }
 
 impl Display for NavmeshGenError {
@@ -99,6 +708,8 @@ impl Display for NavmeshGenError {
             "{}",
             match self {
                 Self::Triangulation(error) => format!("{error}"),
+                Self::GridSize { expected, actual } =>
+                    format!("navability grid has {actual} tiles, but the map has {expected}"),
                 Self::Navmesh(navmesh::Error::TriangleVerticeIndexOutOfBounds(
                     triangle,
                     local_vertex,
@@ -155,12 +766,67 @@ impl From<navmesh::Error> for NavmeshGenError {
 }
 
 /// Generate a navmesh for your tilemap. The input to `navability` is a tile's position.
+/// [`Navability::OneWay`] tiles are navigable in every direction on the returned navmesh;
+/// use [`Navmeshes::find_path`] to respect their direction.
 pub fn generate_navmesh(
     map_size: UVec2,
     tile_size: Vec2,
     navability: impl Fn(UVec2) -> Navability,
     clearance: f32,
 ) -> Result<NavMesh, NavmeshGenError> {
+    generate_navmesh_from_grid(
+        map_size,
+        tile_size,
+        &navability_grid(map_size, navability),
+        clearance,
+    )
+    .map(|entry| entry.navmesh)
+}
+
+// Clearances closer than this share a navmesh
+const CLEARANCE_EPSILON: f32 = 0.001;
+
+// Sorts the clearances and merges those within `CLEARANCE_EPSILON` of each other into the largest
+// of them, so `Navmeshes::mesh` still finds a navmesh for each. Also returns the merged clearances.
+fn sorted_clearances(clearances: impl IntoIterator<Item = f32>) -> (Vec<f32>, Vec<f32>) {
+    let mut clearances = clearances.into_iter().collect::<Vec<_>>();
+    clearances.sort_by(f32::total_cmp);
+
+    let mut kept = Vec::<f32>::with_capacity(clearances.len());
+    let mut merged = Vec::default();
+    for clearance in clearances {
+        if let Some(last) = kept.last_mut() {
+            if clearance - *last < CLEARANCE_EPSILON {
+                #[cfg(all(feature = "bevy", feature = "log"))]
+                warn!(
+                    "clearance {last} is within {CLEARANCE_EPSILON} of {clearance}; merging them"
+                );
+
+                merged.push(*last);
+                *last = clearance;
+                continue;
+            }
+        }
+
+        kept.push(clearance);
+    }
+
+    (kept, merged)
+}
+
+fn navability_grid(map_size: UVec2, navability: impl Fn(UVec2) -> Navability) -> Vec<Navability> {
+    let navability = &navability;
+    (0..map_size.y)
+        .flat_map(|y| (0..map_size.x).map(move |x| navability(UVec2::new(x, y))))
+        .collect()
+}
+
+fn generate_navmesh_from_grid(
+    map_size: UVec2,
+    tile_size: Vec2,
+    navability: &[Navability],
+    clearance: f32,
+) -> Result<NavmeshEntry, NavmeshGenError> {
     let mut vertex_normals =
         vec![VertexNormal::None; ((map_size.x + 1) * (map_size.y + 1)) as usize];
     let vertex_index = |x, y| (y * (map_size.x + 1) + x) as usize;
@@ -179,11 +845,6 @@ pub fn generate_navmesh(
         vertex_normals[vertex_index(map_size.x, y + 1)].add_assn(Ordinal::Northwest);
     }
 
-    let navability = &navability;
-    let navability = (0..map_size.y)
-        .flat_map(|y| (0..map_size.x).map(move |x| navability(UVec2::new(x, y))))
-        .collect::<Vec<_>>();
-
     for y in 0..map_size.y {
         for x in 0..map_size.x {
             if navability[(y * map_size.x + x) as usize] == Navability::Solid {
@@ -231,24 +892,293 @@ pub fn generate_navmesh(
         }
     }
 
-    Ok(NavMesh::new(
-        vertices
+    add_one_way_edges(
+        map_size,
+        tile_size,
+        navability,
+        clearance,
+        &mut vertices,
+        &mut edges,
+    );
+
+    let tile_at = |v1: usize, v2: usize, v3: usize| {
+        let tile = ((vertices[v1] + vertices[v2] + vertices[v3]) / 3. / tile_size).as_uvec2();
+        navability[(tile.y * map_size.x + tile.x) as usize]
+    };
+    let triangles = triangulate_with_edges(
+        &vertices
             .iter()
-            .map(|vertex| Vector3::from(vertex.extend(0.)).into())
-            .collect(),
-        triangulate_with_edges(
-            &vertices
+            .map(|vertex| (vertex.x as f64, vertex.y as f64))
+            .collect::<Vec<_>>(),
+        &edges,
+    )?
+    .into_iter()
+    .filter(|&(v1, v2, v3)| tile_at(v1, v2, v3) != Navability::Solid)
+    .collect::<Vec<_>>();
+
+    let neighbors = triangle_neighbors(&triangles);
+    let blocked_transitions = blocked_transitions(&vertices, &triangles, &neighbors, tile_at);
+
+    Ok(NavmeshEntry {
+        navmesh: NavMesh::new(
+            vertices
                 .iter()
-                .map(|vertex| (vertex.x as f64, vertex.y as f64))
-                .collect::<Vec<_>>(),
-            &edges,
-        )?
-        .into_iter()
-        .filter_map(|(v1, v2, v3)| {
-            let tile = ((vertices[v1] + vertices[v2] + vertices[v3]) / 3. / tile_size).as_uvec2();
-            (navability[(tile.y * map_size.x + tile.x) as usize] == Navability::Navable)
-                .then(|| (v1 as u32, v2 as u32, v3 as u32).into())
+                .map(|vertex| Vector3::from(vertex.extend(0.)).into())
+                .collect(),
+            triangles
+                .into_iter()
+                .map(|(v1, v2, v3)| (v1 as u32, v2 as u32, v3 as u32).into())
+                .collect(),
+        )?,
+        clearance,
+        blocked_transitions,
+        neighbors,
+    })
+}
+
+// Adds fixed edges along the tile sides that bound one-way regions, so that every triangle
+// is within a single region. Sides are clipped to the area outside of the outline.
+fn add_one_way_edges(
+    map_size: UVec2,
+    tile_size: Vec2,
+    navability: &[Navability],
+    clearance: f32,
+    vertices: &mut Vec<Vec2>,
+    edges: &mut Vec<(usize, usize)>,
+) {
+    let navability_at = |x: u32, y: u32| navability[(y * map_size.x + x) as usize];
+    let is_boundary = |a, b| {
+        a != b
+            && a != Navability::Solid
+            && b != Navability::Solid
+            && (matches!(a, Navability::OneWay(_)) || matches!(b, Navability::OneWay(_)))
+    };
+
+    // Tile sides as (axis, position of the side, index of the tile along the side),
+    // where axis 0 means the side is vertical
+    let mut sides = Vec::default();
+    for y in 0..map_size.y {
+        for x in 1..map_size.x {
+            if is_boundary(navability_at(x - 1, y), navability_at(x, y)) {
+                sides.push((0, x, y));
+            }
+        }
+    }
+    for y in 1..map_size.y {
+        for x in 0..map_size.x {
+            if is_boundary(navability_at(x, y - 1), navability_at(x, y)) {
+                sides.push((1, y, x));
+            }
+        }
+    }
+
+    if sides.is_empty() {
+        return;
+    }
+
+    let key = |vertex: Vec2| [(vertex.x + 0.).to_bits(), (vertex.y + 0.).to_bits()];
+    let mut vertex_indices = vertices
+        .iter()
+        .enumerate()
+        .map(|(index, &vertex)| (key(vertex), index))
+        .collect::<HashMap<_, _>>();
+
+    // Edges by the line they lie on, as (axis, position of the line), where axis 0 means
+    // the line is vertical. Every outline edge is axis-aligned.
+    let mut lines = HashMap::<_, Vec<usize>>::default();
+    for (index, &(start, end)) in edges.iter().enumerate() {
+        let axis = match vertices[start].x == vertices[end].x {
+            true => 0,
+            false => 1,
+        };
+        lines
+            .entry((axis, key(vertices[start])[axis]))
+            .or_default()
+            .push(index);
+    }
+
+    // Solid tiles by column, then by row, as their positions along that column or row
+    let mut solid_lines = [
+        vec![Vec::default(); map_size.x as usize],
+        vec![Vec::default(); map_size.y as usize],
+    ];
+    for y in 0..map_size.y {
+        for x in 0..map_size.x {
+            if navability_at(x, y) == Navability::Solid {
+                solid_lines[0][x as usize].push(y);
+                solid_lines[1][y as usize].push(x);
+            }
+        }
+    }
+
+    for (axis, position, index) in sides {
+        let across = axis;
+        let along = 1 - axis;
+        let corner = |along_index: u32| {
+            let mut corner = UVec2::ZERO;
+            corner[across] = position;
+            corner[along] = along_index;
+            corner.as_vec2() * tile_size
+        };
+        let (start, end) = (corner(index), corner(index + 1));
+        let map_end = map_size.as_vec2() * tile_size - clearance;
+
+        if start[across] <= clearance || start[across] >= map_end[across] {
+            continue;
+        }
+
+        // Intervals along the side that are within the outline. Only solid tiles within
+        // the clearance of the side can reach it.
+        let mut blocked = Vec::default();
+        blocked.push((f32::NEG_INFINITY, clearance));
+        blocked.push((map_end[along], f32::INFINITY));
+        let reach = (clearance / tile_size[across]).ceil() as u32 + 1;
+        for line in position.saturating_sub(reach)..(position + reach).min(map_size[across]) {
+            for &along_index in &solid_lines[axis][line as usize] {
+                let mut tile = UVec2::ZERO;
+                tile[across] = line;
+                tile[along] = along_index;
+                let min = tile.as_vec2() * tile_size + Vec2::NEG_ONE * clearance;
+                let max = (tile + 1).as_vec2() * tile_size + Vec2::ONE * clearance;
+                if min[across] <= start[across] && start[across] <= max[across] {
+                    blocked.push((min[along], max[along]));
+                }
+            }
+        }
+        blocked.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        let mut free_start = start[along];
+        let mut free = Vec::default();
+        for (min, max) in blocked {
+            if min > free_start {
+                free.push((free_start, min.min(end[along])));
+            }
+            free_start = free_start.max(max);
+        }
+        if free_start < end[along] {
+            free.push((free_start, end[along]));
+        }
+
+        for (min, max) in free {
+            if min >= max {
+                continue;
+            }
+
+            let [edge_start, edge_end] = [min, max].map(|along_position| {
+                let mut vertex = start;
+                vertex[along] = along_position;
+                if let Some(&index) = vertex_indices.get(&key(vertex)) {
+                    return index;
+                }
+
+                // The new vertex may lie on an edge, which must be split around it
+                let index = vertices.len();
+                vertices.push(vertex);
+                vertex_indices.insert(key(vertex), index);
+                for line in [(0, key(vertex)[0]), (1, key(vertex)[1])] {
+                    let Some(line_edges) = lines.get_mut(&line) else { continue };
+                    let Some(&split) = line_edges.iter().find(|&&edge| {
+                        let (start, end) = edges[edge];
+                        (vertices[start] - vertex).dot(vertices[end] - vertex) < 0.
+                    }) else { continue };
+
+                    let (start, end) = edges[split];
+                    edges[split] = (start, index);
+                    line_edges.push(edges.len());
+                    edges.push((index, end));
+                    break;
+                }
+
+                index
+            });
+
+            // Doubled edges are fixed, but `cdt` doesn't treat them as the inside or outside
+            // of the navigable area
+            let line_edges = lines.entry((axis, key(start)[axis])).or_default();
+            for _ in 0..2 {
+                line_edges.push(edges.len());
+                edges.push((edge_start, edge_end));
+            }
+        }
+    }
+}
+
+// Finds pairs of adjacent triangles that cannot be crossed from the first to the second
+// because the crossing goes against the direction of a one-way tile on either side
+fn blocked_transitions(
+    vertices: &[Vec2],
+    triangles: &[(usize, usize, usize)],
+    neighbors: &[[Option<usize>; 3]],
+    tile_at: impl Fn(usize, usize, usize) -> Navability,
+) -> HashSet<(usize, usize)> {
+    let direction = |(v1, v2, v3)| match tile_at(v1, v2, v3) {
+        Navability::OneWay(direction) => Some(direction.as_vec2()),
+        _ => None,
+    };
+    let directions = triangles.iter().copied().map(direction).collect::<Vec<_>>();
+    let mut blocked_transitions = HashSet::default();
+
+    if directions.iter().all(Option::is_none) {
+        return blocked_transitions;
+    }
+
+    let centroid =
+        |(v1, v2, v3): (usize, usize, usize)| (vertices[v1] + vertices[v2] + vertices[v3]) / 3.;
+
+    for (t1, &(v1, v2, v3)) in triangles.iter().enumerate() {
+        for ((start, end), t2) in [(v1, v2), (v2, v3), (v3, v1)]
+            .into_iter()
+            .zip(neighbors[t1])
+        {
+            let Some(t2) = t2 else { continue };
+            if t2 < t1 || directions[t1] == directions[t2] {
+                continue;
+            }
+
+            // Normal of the shared edge, pointing from `t1` to `t2`
+            let normal = (vertices[end] - vertices[start]).perp();
+            let normal = normal
+                * normal
+                    .dot(centroid(triangles[t2]) - centroid(triangles[t1]))
+                    .signum();
+
+            for (from, to, normal) in [(t1, t2, normal), (t2, t1, -normal)] {
+                if [directions[from], directions[to]]
+                    .into_iter()
+                    .flatten()
+                    .any(|direction| normal.dot(direction) < 0.)
+                {
+                    blocked_transitions.insert((from, to));
+                }
+            }
+        }
+    }
+
+    blocked_transitions
+}
+
+fn triangle_neighbors(triangles: &[(usize, usize, usize)]) -> Vec<[Option<usize>; 3]> {
+    let edges = |(v1, v2, v3)| [(v1, v2), (v2, v3), (v3, v1)];
+    let mut edge_triangles = HashMap::<(usize, usize), Vec<usize>>::default();
+    for (triangle, &vertices) in triangles.iter().enumerate() {
+        for (start, end) in edges(vertices) {
+            edge_triangles
+                .entry((start.min(end), start.max(end)))
+                .or_default()
+                .push(triangle);
+        }
+    }
+
+    triangles
+        .iter()
+        .enumerate()
+        .map(|(triangle, &vertices)| {
+            edges(vertices).map(|(start, end)| {
+                edge_triangles[&(start.min(end), start.max(end))]
+                    .iter()
+                    .copied()
+                    .find(|&other| other != triangle)
+            })
         })
-        .collect(),
-    )?)
+        .collect()
 }
@@ -1,44 +1,253 @@
-use std::{collections::VecDeque, error::Error, time::Duration};
+use std::{
+    collections::VecDeque,
+    error::Error,
+    f32::consts::{FRAC_PI_4, TAU},
+    fmt::{self, Debug, Formatter},
+    sync::Arc,
+    time::Duration,
+};
 
-use mint::Vector3;
+#[cfg(feature = "diagnostics")]
+use bevy::diagnostic::Diagnostics;
+use bevy::{
+    ecs::{
+        query::QueryEntityError,
+        schedule::{BoxedScheduleLabel, ScheduleLabel},
+        system::SystemParam,
+    },
+    utils::{HashMap, HashSet},
+};
 use navmesh::{NavPathMode, NavQuery};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-use crate::{prelude::*, set::MapNavSet};
+#[cfg(feature = "diagnostics")]
+use crate::diagnostics::MapNavDiagnostics;
+use crate::{mesh::ON_MESH_TOLERANCE, prelude::*, set::MapNavSet};
 
-pub(crate) fn nav_plugin<P: Position2<Position = Vec2>>(app: &mut App) {
-    app.add_systems(
-        Update,
-        (apply_deferred, generate_paths::<P>, nav::<P>)
+pub(crate) fn nav_plugin<P: Position2<Position = Vec2>>(
+    app: &mut App,
+    nav_schedule: BoxedScheduleLabel,
+) {
+    app.add_event::<NavStarted>()
+        .add_event::<NavArrived>()
+        .add_event::<NavFailed>()
+        .add_event::<NavBlocked>()
+        .add_event::<NavGroupArrived>();
+
+    // Only `state` needs commands applied before paths are generated. `stop_on_removed_maps`
+    // checks `NavEnabled` itself, so it doesn't miss maps removed while paused.
+    #[cfg(feature = "state")]
+    let generate_paths = (
+        (stop_on_removed_maps, advance_target_queues.run_if(nav_enabled)),
+        apply_deferred,
+        (generate_paths::<P>, share_paths)
             .chain()
-            .in_set(MapNavSet),
-    );
+            .run_if(nav_enabled),
+    )
+        .chain();
+    #[cfg(not(feature = "state"))]
+    let generate_paths = (
+        (stop_on_removed_maps, advance_target_queues.run_if(nav_enabled)),
+        (generate_paths::<P>, share_paths)
+            .chain()
+            .run_if(nav_enabled),
+    )
+        .chain();
+
+    if nav_schedule.as_ref() == &Update as &dyn ScheduleLabel {
+        app.add_systems(
+            Update,
+            (
+                generate_paths,
+                nav::<P>,
+                (record_trails::<P>, update_motion_states),
+                (face_targets::<P>, face_movement),
+                group_arrivals,
+            )
+                .chain()
+                .in_set(MapNavSet)
+                .run_if(nav_enabled),
+        );
+        return;
+    }
+
+    if nav_schedule.as_ref() == &FixedUpdate as &dyn ScheduleLabel {
+        app.init_resource::<FixedNav>();
+    }
+
+    app.add_systems(Update, generate_paths.in_set(MapNavSet))
+        .add_systems(
+            nav_schedule,
+            (
+                nav::<P>,
+                (record_trails::<P>, update_motion_states),
+                (face_targets::<P>, face_movement),
+                group_arrivals,
+            )
+                .chain()
+                .in_set(MapNavSet)
+                .run_if(nav_enabled),
+        );
+}
+
+// Present when `nav` runs in `FixedUpdate`, so it steps by the fixed period
+#[derive(Default, Resource)]
+struct FixedNav;
+
+/// Insert this resource to pause all pathfinding and navigation without pausing [`Time`]. While
+/// it's `false`, the systems in [`MapNavSet`] don't run, but paths and velocities are kept. Map
+/// changes made while paused are handled when navigation resumes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Resource)]
+pub struct NavEnabled(pub bool);
+
+impl Default for NavEnabled {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// Insert this resource to drive navigation by your own clock instead of [`Time`], for
+/// deterministic tests or replays. Call [`NavTimeOverride::step`] before each update.
+#[derive(Clone, Copy, Debug, Default, Resource)]
+pub struct NavTimeOverride {
+    /// Time that navigators move by each time navigation runs
+    pub delta: Duration,
+    /// Total time elapsed, which [`Pathfind::next_repath`] is compared to
+    pub elapsed: Duration,
 }
 
-/// A target to navigate to
-#[derive(Clone, Copy, Debug, PartialEq, Reflect)]
+impl NavTimeOverride {
+    /// Advance the clock by `delta`
+    pub fn step(&mut self, delta: Duration) {
+        self.delta = delta;
+        self.elapsed += delta;
+    }
+}
+
+/// A target to navigate to. If an entity that it refers to is despawned, the navigator stops,
+/// and a [`NavFailed`] is sent.
+#[derive(Clone, Debug, PartialEq, Reflect)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum PathTarget {
     /// A position
     Static(Vec2),
     /// An entity that has a position
     Dynamic(Entity),
+    /// A slot at `offset` from a leader entity, rotated to the direction of the leader's
+    /// [`Nav::velocity`], so `offset` is relative to a leader facing +X
+    FormationSlot {
+        /// Entity that has a position, and optionally a [`Nav`]
+        leader: Entity,
+        /// Offset from the leader, relative to the leader's heading
+        offset: Vec2,
+    },
+    /// Targets in order of preference. The path leads to the first one with a valid path, whose
+    /// index is stored in [`Pathfind::priority_index`].
+    Priority(#[reflect(ignore)] Vec<PathTarget>),
+    /// Circle around a point, by targeting the point on the circle an eighth of a turn ahead of the
+    /// navigator. Use a `repath_frequency` to keep it moving.
+    Orbit {
+        /// Center of the circle
+        center: Vec2,
+        /// Radius of the circle
+        radius: f32,
+        /// Whether to circle clockwise
+        clockwise: bool,
+    },
+    /// The closest point on the edge of the navmesh, including around obstacles. In a
+    /// [`FaceTarget`], this is the entity's own position.
+    NearestBoundary,
+    /// A circular area to stay in. The navigator heads toward `center`, stops once it's inside, and
+    /// heads back in if it's pushed out.
+    Area {
+        /// Center of the area
+        center: Vec2,
+        /// Radius of the area
+        radius: f32,
+    },
+    /// The point `offset` from `protect` toward `from`, or halfway to `from` if it's closer. If
+    /// `from` is despawned, this is `protect` itself.
+    Interpose {
+        /// Entity that has a position, to stand near
+        protect: Entity,
+        /// Entity that has a position, to stand in the way of
+        from: Entity,
+        /// Distance from `protect` to stand at
+        offset: f32,
+    },
+    /// The point `t` of the way from `a`'s point to `b`'s. This blends points, not paths, so the
+    /// point may be unreachable even when both targets are reachable.
+    Blend {
+        /// Target at `t` of 0
+        #[reflect(ignore, default = "blend_target_default")]
+        a: Box<PathTarget>,
+        /// Target at `t` of 1
+        #[reflect(ignore, default = "blend_target_default")]
+        b: Box<PathTarget>,
+        /// How far to blend from `a` to `b`, usually from 0 to 1
+        t: f32,
+    },
+}
+
+fn blend_target_default() -> Box<PathTarget> {
+    Box::new(PathTarget::Static(Vec2::ZERO))
 }
 
 /// Add this component to your entity to have it generate paths. Works as a state
 /// in `seldom_state`.
+///
+/// To move the entity with your own system, add this without a [`Nav`], pop waypoints from `path`
+/// as the entity reaches them, and check [`Pathfind::done`].
 #[derive(Clone, Component, Debug)]
 pub struct Pathfind {
-    /// Tilemap with the [`Navmeshes`] component
+    /// Tilemap with the [`Navmeshes`] component. Changing it causes a repath.
     pub map: Entity,
-    /// Clearance radius
+    /// Clearance radius. Changing it causes a repath.
     pub radius: f32,
-    /// How often to regenerate the path, if ever
+    /// How often to regenerate the path, if ever. Frequencies shorter than a frame repath every
+    /// frame. The path is also regenerated when the map's [`Navmeshes`] change.
     pub repath_frequency: Option<Duration>,
-    /// Next time to repath
+    /// Next time to repath, if `repath_frequency` is set
     pub next_repath: Duration,
+    /// Whether a path has been generated since this was created. If not, a path is generated
+    /// on the next update, regardless of `repath_frequency`.
+    pub has_pathed: bool,
+    /// Whether to repath on the next update regardless of `repath_frequency`. Cleared when
+    /// it repaths.
+    pub force_repath: bool,
+    /// Fraction of `repath_frequency`, from 0 to 1, by which to randomly shorten the first repath
+    /// interval, so entities that start together don't repath together
+    pub repath_jitter: f32,
     /// Target to navigate to
     pub target: PathTarget,
+    /// Whether the entity stopped repathing because its target was despawned. Cleared
+    /// by [`Pathfind::set_target`].
+    pub stopped: bool,
+    /// If the target is a [`PathTarget::Priority`], index of the target that the path leads to
+    pub priority_index: Option<usize>,
     /// Generated path
     pub path: VecDeque<Vec2>,
+    /// Clearance radius that the path was generated with, if a path has been generated
+    pub path_radius: Option<f32>,
+    /// Map that the path was generated on, if a path has been generated
+    pub path_map: Option<Entity>,
+    /// Whether the last generated path reaches its target, if a path was found
+    pub path_status: Option<PathStatus>,
+    /// Waypoint that the entity most recently passed, or where its path started
+    pub last_waypoint: Option<Vec2>,
+    /// Whether to path as close as possible to an unreachable [`PathTarget::Dynamic`] target, with
+    /// a [`PathStatus::Partial`] path. Defaults to `false`.
+    pub nearest_fallback: bool,
+    /// Radius of the circle that entities with the same [`PathTarget::Dynamic`] target spread out
+    /// around it on. Defaults to 0, which paths to the target itself.
+    pub surround_radius: f32,
+    /// Whether to move the start of the path onto the navmesh before pathfinding. Defaults to
+    /// `true`.
+    pub snap_start: bool,
+    /// Distance from a [`PathTarget::Dynamic`] target's current position at which the entity has
+    /// arrived. Defaults to 0, which waits for the path to run out.
+    pub done_distance: f32,
     /// Quality of querying a point on the navmesh
     pub query: NavQuery,
     /// Quality of finding a path
@@ -60,27 +269,692 @@ impl Pathfind {
             radius,
             repath_frequency,
             next_repath: Duration::ZERO,
+            has_pathed: false,
+            force_repath: false,
+            repath_jitter: 0.,
             target,
+            stopped: false,
+            priority_index: None,
             path: default(),
+            path_radius: None,
+            path_map: None,
+            path_status: None,
+            last_waypoint: None,
+            nearest_fallback: false,
+            surround_radius: 0.,
+            snap_start: true,
+            done_distance: 0.,
             query,
             path_mode,
         }
     }
+
+    /// Whether a path has been generated and no waypoints are left, because the entity arrived or
+    /// no path was found. Like [`Nav::done`], but doesn't require a [`Nav`].
+    pub fn done(&self) -> bool {
+        self.has_pathed && !self.force_repath && self.path.is_empty()
+    }
+
+    /// Point `distance` along the remaining path, or the end of the path if it's shorter. Returns
+    /// `None` if the path is empty.
+    pub fn lookahead_point(&self, mut distance: f32) -> Option<Vec2> {
+        let mut point = *self.path.front()?;
+        for &waypoint in self.path.iter().skip(1) {
+            let segment = point.distance(waypoint);
+            if distance < segment {
+                return Some(point.lerp(waypoint, distance / segment));
+            }
+
+            distance -= segment;
+            point = waypoint;
+        }
+
+        Some(point)
+    }
+
+    /// Navigate to a new target on the next update. Unlike setting `target` directly, this resumes
+    /// navigating after the previous target was despawned.
+    pub fn set_target(&mut self, target: PathTarget) {
+        self.target = target;
+        self.stopped = false;
+        self.priority_index = None;
+        self.force_repath = true;
+    }
+
+    /// Set the `repath_jitter`
+    pub fn with_repath_jitter(mut self, repath_jitter: f32) -> Self {
+        self.repath_jitter = repath_jitter;
+        self
+    }
+
+    /// Set the `nearest_fallback`
+    pub fn with_nearest_fallback(mut self, nearest_fallback: bool) -> Self {
+        self.nearest_fallback = nearest_fallback;
+        self
+    }
+
+    /// Set the `surround_radius`
+    pub fn with_surround_radius(mut self, surround_radius: f32) -> Self {
+        self.surround_radius = surround_radius;
+        self
+    }
+
+    /// Set the `snap_start`
+    pub fn with_snap_start(mut self, snap_start: bool) -> Self {
+        self.snap_start = snap_start;
+        self
+    }
+
+    /// Set the `done_distance`
+    pub fn with_done_distance(mut self, done_distance: f32) -> Self {
+        self.done_distance = done_distance;
+        self
+    }
+
+    /// Follow the waypoints recorded in `history` back to where the entity started navigating. This
+    /// stops repathing and clears `history`.
+    pub fn retrace(&mut self, history: &mut NavHistory) {
+        self.path = history.waypoints.drain(..).rev().collect();
+        if let Some(&origin) = self.path.back() {
+            self.target = PathTarget::Static(origin);
+        }
+        self.repath_frequency = None;
+        self.stopped = false;
+        self.has_pathed = true;
+        self.force_repath = false;
+        self.path_status = (!self.path.is_empty()).then_some(PathStatus::Complete);
+        self.last_waypoint = None;
+    }
+}
+
+/// Add this component alongside [`Pathfind`] to follow a leader's path instead of pathfinding. The
+/// leader's path is copied, moved by `offset`, whenever it changes, and the follower's
+/// [`Pathfind::target`] and `repath_frequency` are ignored. If the leader has no [`Pathfind`], or
+/// has a `SharedPath`, the follower stops, and a [`NavFailed`] is sent.
+#[derive(Clone, Component, Debug)]
+pub struct SharedPath {
+    /// Entity whose path to follow
+    pub leader: Entity,
+    /// Offset of the follower's path from the leader's
+    pub offset: Vec2,
+    // Length and destination of the leader's path when it was last copied
+    copied: Option<(usize, Vec2)>,
+}
+
+impl SharedPath {
+    /// Create a `SharedPath`
+    pub fn new(leader: Entity, offset: Vec2) -> Self {
+        Self {
+            leader,
+            offset,
+            copied: None,
+        }
+    }
+}
+
+/// Add this component alongside [`Pathfind`] to visit several targets in order. The front of the
+/// queue is copied into [`Pathfind::target`], and is removed when the entity arrives or no path to
+/// it is found.
+#[derive(Clone, Component, Debug, Default)]
+pub struct TargetQueue {
+    /// Targets to visit, starting with the current one
+    pub targets: VecDeque<PathTarget>,
+}
+
+impl TargetQueue {
+    /// Create a `TargetQueue`
+    pub fn new(targets: impl IntoIterator<Item = PathTarget>) -> Self {
+        Self {
+            targets: targets.into_iter().collect(),
+        }
+    }
+
+    /// Visit `target` before the current target, which is resumed afterward
+    pub fn push_front(&mut self, target: PathTarget) {
+        self.targets.push_front(target);
+    }
+
+    /// Visit `target` after every other queued target
+    pub fn push_back(&mut self, target: PathTarget) {
+        self.targets.push_back(target);
+    }
+
+    /// Create a `TargetQueue` that visits every point in `points`, starting from `from`, in a short
+    /// order found by repeatedly heading to the closest point by [`Navmeshes::path_cost`]. Points
+    /// without a path are visited last.
+    pub fn coverage(
+        navmeshes: &Navmeshes,
+        clearance: f32,
+        from: Vec2,
+        points: impl IntoIterator<Item = Vec2>,
+    ) -> Self {
+        let mut points = points.into_iter().collect::<Vec<_>>();
+        let mut targets = VecDeque::with_capacity(points.len());
+        let mut current = from;
+
+        while !points.is_empty() {
+            points.sort_by(|a, b| {
+                a.distance_squared(current)
+                    .total_cmp(&b.distance_squared(current))
+            });
+            let next = points
+                .iter()
+                .take(COVERAGE_CANDIDATES)
+                .enumerate()
+                .filter_map(|(index, &point)| {
+                    Some((index, navmeshes.path_cost(clearance, current, point)?))
+                })
+                .min_by(|(_, cost), (_, other_cost)| cost.total_cmp(other_cost))
+                .map(|(index, _)| index);
+
+            let Some(next) = next else { break };
+            current = points.swap_remove(next);
+            targets.push_back(PathTarget::Static(current));
+        }
+
+        targets.extend(points.into_iter().map(PathTarget::Static));
+        Self { targets }
+    }
+}
+
+// How many of the closest remaining points `TargetQueue::coverage` compares by path cost
+const COVERAGE_CANDIDATES: usize = 8;
+
+/// Serializable form of a [`Pathfind`], without its path, which is regenerated on load. Remap its
+/// entities to the loaded world's entities before converting it back.
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PathfindSnapshot {
+    /// [`Pathfind::map`]
+    pub map: Entity,
+    /// [`Pathfind::radius`]
+    pub radius: f32,
+    /// [`Pathfind::repath_frequency`]
+    pub repath_frequency: Option<Duration>,
+    /// [`Pathfind::repath_jitter`]
+    pub repath_jitter: f32,
+    /// [`Pathfind::target`]
+    pub target: PathTarget,
+    /// [`Pathfind::nearest_fallback`]
+    pub nearest_fallback: bool,
+    /// [`Pathfind::surround_radius`]
+    pub surround_radius: f32,
+    /// [`Pathfind::snap_start`]
+    pub snap_start: bool,
+    /// [`Pathfind::done_distance`]
+    pub done_distance: f32,
+    /// [`Pathfind::query`]
+    pub query: NavQuery,
+    /// [`Pathfind::path_mode`]
+    pub path_mode: NavPathMode,
+}
+
+#[cfg(feature = "serde")]
+impl From<&Pathfind> for PathfindSnapshot {
+    fn from(pathfind: &Pathfind) -> Self {
+        Self {
+            map: pathfind.map,
+            radius: pathfind.radius,
+            repath_frequency: pathfind.repath_frequency,
+            repath_jitter: pathfind.repath_jitter,
+            target: pathfind.target.clone(),
+            nearest_fallback: pathfind.nearest_fallback,
+            surround_radius: pathfind.surround_radius,
+            snap_start: pathfind.snap_start,
+            done_distance: pathfind.done_distance,
+            query: pathfind.query,
+            path_mode: pathfind.path_mode,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<PathfindSnapshot> for Pathfind {
+    fn from(snapshot: PathfindSnapshot) -> Self {
+        Self {
+            repath_jitter: snapshot.repath_jitter,
+            nearest_fallback: snapshot.nearest_fallback,
+            surround_radius: snapshot.surround_radius,
+            snap_start: snapshot.snap_start,
+            done_distance: snapshot.done_distance,
+            ..Self::new(
+                snapshot.map,
+                snapshot.radius,
+                snapshot.repath_frequency,
+                snapshot.target,
+                snapshot.query,
+                snapshot.path_mode,
+            )
+        }
+    }
+}
+
+/// Whether a generated path reaches its target
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Reflect)]
+pub enum PathStatus {
+    /// The path ends at the target
+    Complete,
+    /// The path ends short of the target, so the entity won't reach it
+    Partial,
 }
 
 /// Add this component and [`Pathfind`] to your entity to have it navigate
 #[derive(Clone, Component, Copy, Debug, Reflect)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Nav {
     /// Speed by which to navigate
     pub speed: f32,
+    /// Maximum speed, which [`VelocityOverride`] velocities are clamped to. Defaults to `speed`.
+    pub max_speed: f32,
+    /// Distance from a waypoint at which to start heading to the next one, cutting the corner.
+    /// Defaults to 0.
+    pub corner_radius: f32,
+    /// Distance from the destination within which to head straight for it, if nothing is in the
+    /// way. Defaults to 0.
+    pub seek_distance: f32,
+    /// Speed by which to move back onto the navmesh when off of it. Defaults to 0, which disables
+    /// recovery.
+    pub recovery_speed: f32,
+    /// Gap to keep behind navigators ahead, so navigators in a corridor move single-file. Defaults
+    /// to 0, which disables spacing.
+    pub spacing: f32,
+    /// Priority when spacing. The entity only keeps its `spacing` behind navigators with at least
+    /// this priority. Defaults to 0.
+    pub push_priority: i32,
+    /// Rate, in units per second squared, at which to coast to a stop after arriving. Defaults to
+    /// 0, which stops on arrival.
+    pub arrival_deceleration: f32,
+    /// Maximum distance to stray from the path when the velocity is overridden or limited. Defaults
+    /// to 0, which disables the limit.
+    pub max_lateral_offset: f32,
+    /// Maximum rate, in units per second squared, at which the velocity may change. Defaults to 0,
+    /// which disables the limit.
+    pub max_acceleration: f32,
+    /// Angle, in radians, from straight behind within which an entity with a [`Facing`] backs up
+    /// instead of turning around. Defaults to 0.
+    pub reverse_angle: f32,
     /// Whether the entity has navigated to the destination
     pub done: bool,
+    /// Velocity of the entity's last movement, in units per second
+    pub velocity: Vec2,
 }
 
 impl Nav {
     /// Create a `Nav`
     pub fn new(speed: f32) -> Self {
-        Self { speed, done: false }
+        Self {
+            speed,
+            max_speed: speed,
+            corner_radius: 0.,
+            seek_distance: 0.,
+            recovery_speed: 0.,
+            spacing: 0.,
+            push_priority: 0,
+            arrival_deceleration: 0.,
+            max_lateral_offset: 0.,
+            max_acceleration: 0.,
+            reverse_angle: 0.,
+            done: false,
+            velocity: Vec2::ZERO,
+        }
+    }
+
+    /// Set the `max_speed`
+    pub fn with_max_speed(mut self, max_speed: f32) -> Self {
+        self.max_speed = max_speed;
+        self
+    }
+
+    /// Set the `corner_radius`
+    pub fn with_corner_radius(mut self, corner_radius: f32) -> Self {
+        self.corner_radius = corner_radius;
+        self
+    }
+
+    /// Set the `seek_distance`
+    pub fn with_seek_distance(mut self, seek_distance: f32) -> Self {
+        self.seek_distance = seek_distance;
+        self
+    }
+
+    /// Set the `recovery_speed`
+    pub fn with_recovery_speed(mut self, recovery_speed: f32) -> Self {
+        self.recovery_speed = recovery_speed;
+        self
+    }
+
+    /// Set the `spacing`
+    pub fn with_spacing(mut self, spacing: f32) -> Self {
+        self.spacing = spacing;
+        self
+    }
+
+    /// Set the `push_priority`
+    pub fn with_push_priority(mut self, push_priority: i32) -> Self {
+        self.push_priority = push_priority;
+        self
+    }
+
+    /// Set the `arrival_deceleration`
+    pub fn with_arrival_deceleration(mut self, arrival_deceleration: f32) -> Self {
+        self.arrival_deceleration = arrival_deceleration;
+        self
+    }
+
+    /// Set the `max_lateral_offset`
+    pub fn with_max_lateral_offset(mut self, max_lateral_offset: f32) -> Self {
+        self.max_lateral_offset = max_lateral_offset;
+        self
+    }
+
+    /// Set the `max_acceleration`
+    pub fn with_max_acceleration(mut self, max_acceleration: f32) -> Self {
+        self.max_acceleration = max_acceleration;
+        self
+    }
+
+    /// Set the `reverse_angle`
+    pub fn with_reverse_angle(mut self, reverse_angle: f32) -> Self {
+        self.reverse_angle = reverse_angle;
+        self
+    }
+
+    /// Instantly move the entity to `to`, and drop its path. It stops, and paths from `to` on the
+    /// next update.
+    pub fn teleport(
+        &mut self,
+        pathfind: &mut Pathfind,
+        position: &mut impl Position2<Position = Vec2>,
+        to: Vec2,
+    ) {
+        position.set(to);
+        self.velocity = Vec2::ZERO;
+        self.done = false;
+        pathfind.path.clear();
+        pathfind.path_status = None;
+        pathfind.last_waypoint = None;
+        pathfind.priority_index = None;
+        pathfind.force_repath = true;
+    }
+}
+
+/// Add this component alongside [`Nav`] to adjust the velocity, in units per second, that path
+/// following computes, to blend in strafing, for example. The result is clamped to
+/// [`Nav::max_speed`] and stops at the edge of the navmesh.
+#[derive(Clone, Component)]
+pub struct VelocityOverride(pub Arc<dyn Fn(Entity, Vec2) -> Vec2 + Send + Sync>);
+
+impl VelocityOverride {
+    /// Create a `VelocityOverride`
+    pub fn new(velocity_override: impl Fn(Entity, Vec2) -> Vec2 + Send + Sync + 'static) -> Self {
+        Self(Arc::new(velocity_override))
+    }
+}
+
+impl Debug for VelocityOverride {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("VelocityOverride").finish_non_exhaustive()
+    }
+}
+
+/// Insert this resource to rewrite every generated path before it's followed. The function receives
+/// the entity and its new path. Waypoints moved off of the navmesh may lead the entity into
+/// obstacles.
+#[derive(Clone, Resource)]
+pub struct PathPostProcessor(pub Arc<PostProcessFn>);
+
+// Receives the entity and its new path
+type PostProcessFn = dyn Fn(Entity, &mut VecDeque<Vec2>) + Send + Sync;
+
+impl PathPostProcessor {
+    /// Create a `PathPostProcessor`
+    pub fn new(
+        post_processor: impl Fn(Entity, &mut VecDeque<Vec2>) + Send + Sync + 'static,
+    ) -> Self {
+        Self(Arc::new(post_processor))
+    }
+}
+
+impl Debug for PathPostProcessor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PathPostProcessor").finish_non_exhaustive()
+    }
+}
+
+/// Add this component alongside [`Nav`] to record the waypoints that the entity passes,
+/// so it can return the way it came with [`Pathfind::retrace`]
+#[derive(Clone, Component, Debug, Default, Reflect)]
+pub struct NavHistory {
+    /// Passed waypoints, oldest first, starting with where the entity started navigating
+    pub waypoints: Vec<Vec2>,
+}
+
+/// Add this component alongside [`Nav`] to record the positions that the entity travels through,
+/// unlike [`NavHistory`], which records the waypoints that it passes
+#[derive(Clone, Component, Debug)]
+pub struct NavTrail {
+    /// Recorded positions, oldest first
+    pub points: VecDeque<Vec2>,
+    /// Maximum number of positions to keep. The oldest are dropped past this.
+    pub max_len: usize,
+    /// Time between recorded positions. Defaults to zero, which records every time
+    /// navigation runs.
+    pub interval: Duration,
+    /// Time since the last recorded position
+    pub since_sample: Duration,
+}
+
+impl NavTrail {
+    /// Create a `NavTrail`
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            points: default(),
+            max_len,
+            interval: Duration::ZERO,
+            since_sample: Duration::ZERO,
+        }
+    }
+
+    /// Set the `interval`
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+}
+
+/// Add this component alongside [`Nav`] to keep track of whether the entity is moving, to pick
+/// animations, for example
+#[derive(Clone, Component, Copy, Debug, Default, PartialEq, Reflect)]
+pub enum NavMotionState {
+    /// The entity didn't move in its last movement
+    #[default]
+    Idle,
+    /// The entity moved, at this fraction of [`Nav::speed`]
+    Moving(f32),
+}
+
+/// Add this component alongside [`Nav`] to have navigation write the entity's next position and
+/// velocity here instead of moving it. Apply them after [`MapNavSet`].
+#[derive(Clone, Component, Copy, Debug, Default, Reflect)]
+pub struct NavOutput {
+    /// Position the entity should move to this frame
+    pub position: Vec2,
+    /// Velocity of this frame's movement, in units per second
+    pub velocity: Vec2,
+}
+
+/// Add this component alongside [`Facing`] to have the entity face a target regardless of how it
+/// moves. [`Facing`] is left unchanged while the target is missing.
+#[derive(Clone, Component, Debug, PartialEq, Reflect)]
+pub struct FaceTarget(pub PathTarget);
+
+/// Angle, in radians counterclockwise from +X, that the entity faces. Updated from its
+/// [`FaceTarget`], or else from the direction that it navigates in.
+#[derive(Clone, Component, Copy, Debug, Default, PartialEq, Reflect)]
+pub struct Facing(pub f32);
+
+/// Event sent when an entity without a path gets one
+#[derive(Clone, Copy, Debug, Event)]
+pub struct NavStarted {
+    /// The navigating entity
+    pub entity: Entity,
+    /// Number of waypoints in the new path
+    pub path_len: usize,
+}
+
+/// Event sent when an entity comes within [`Pathfind::done_distance`] of its
+/// [`PathTarget::Dynamic`] target
+#[derive(Clone, Copy, Debug, Event)]
+pub struct NavArrived {
+    /// The navigating entity
+    pub entity: Entity,
+    /// The target that it arrived at
+    pub target: Entity,
+}
+
+/// Event sent when an entity stops navigating because it cannot continue
+#[derive(Clone, Copy, Debug, Event)]
+pub struct NavFailed {
+    /// The navigating entity
+    pub entity: Entity,
+    /// Why navigation failed
+    pub reason: NavFailReason,
+}
+
+/// Event sent when an entity has been halted for several frames behind a navigator that it keeps
+/// its [`Nav::spacing`] from
+#[derive(Clone, Copy, Debug, Event)]
+pub struct NavBlocked {
+    /// The halted entity
+    pub entity: Entity,
+    /// The navigator ahead of it
+    pub by: Entity,
+}
+
+/// Add this component alongside [`Nav`] to be sent a [`NavGroupArrived`] when every entity in the
+/// group has arrived
+#[derive(Clone, Component, Copy, Debug, Eq, Hash, PartialEq, Reflect)]
+pub struct NavGroup(pub u64);
+
+/// Event sent when every entity in a [`NavGroup`] is [`Nav::done`]
+#[derive(Clone, Copy, Debug, Event)]
+pub struct NavGroupArrived {
+    /// The group that arrived
+    pub group: NavGroup,
+}
+
+/// Reason for a [`NavFailed`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NavFailReason {
+    /// The entity referenced by the [`PathTarget`] was despawned. The entity stops repathing
+    /// until it's given a new target with [`Pathfind::set_target`].
+    MissingTarget,
+    /// The map doesn't have a navmesh with at least the [`Pathfind::radius`] of clearance. Sent
+    /// once per radius and map.
+    MissingClearance,
+    /// The [`Navmeshes`] of the map were removed. The entity stops until [`Pathfind::map`] is
+    /// changed.
+    MissingNavmesh,
+    /// The entity's position isn't finite. It paths again on its next repath after it's fixed, or
+    /// when [`Pathfind::force_repath`] is set.
+    InvalidPosition,
+}
+
+/// System parameter for reading every navigating entity's path
+#[derive(SystemParam)]
+pub struct NavPaths<'w, 's> {
+    pathfinds: Query<'w, 's, (Entity, &'static Pathfind)>,
+}
+
+impl<'w, 's> NavPaths<'w, 's> {
+    /// Iterates over the remaining waypoints of every entity that has a path
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &VecDeque<Vec2>)> {
+        self.pathfinds
+            .iter()
+            .filter(|(_, pathfind)| !pathfind.path.is_empty())
+            .map(|(entity, pathfind)| (entity, &pathfind.path))
+    }
+
+    /// Gets the remaining waypoints of the given entity, if it has a path
+    pub fn get(&self, entity: Entity) -> Option<&VecDeque<Vec2>> {
+        self.pathfinds
+            .get(entity)
+            .ok()
+            .map(|(_, pathfind)| &pathfind.path)
+            .filter(|path| !path.is_empty())
+    }
+}
+
+/// Insert this resource to share paths between navigators on the same map with the same clearance
+/// radius that path between the same square cells. Only complete paths are cached, and not for
+/// [`PathTarget::Priority`], [`PathTarget::Orbit`], or [`PathTarget::Area`] targets.
+///
+/// Cached paths ignore [`Pathfind::query`] and [`Pathfind::path_mode`], and a map's paths are
+/// dropped when its [`Navmeshes`] change. Keep `cell_size` small, since the ends of a reused path
+/// are straight lines that aren't checked against the navmesh. Each path costs memory proportional
+/// to its length. When `capacity` paths are cached, the cache is cleared before another is added.
+#[derive(Clone, Debug, Resource)]
+pub struct PathCache {
+    /// Width of the square cells that positions are grouped into
+    pub cell_size: f32,
+    /// Maximum number of cached paths
+    pub capacity: usize,
+    paths: HashMap<PathCacheKey, VecDeque<Vec2>>,
+}
+
+// Map, clearance radius bits, start cell, and goal cell
+type PathCacheKey = (Entity, u32, IVec2, IVec2);
+
+impl PathCache {
+    /// Create a `PathCache`
+    pub fn new(cell_size: f32, capacity: usize) -> Self {
+        Self {
+            cell_size,
+            capacity,
+            paths: default(),
+        }
+    }
+
+    /// Gets the number of cached paths
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    /// Whether no paths are cached
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// Drops every cached path
+    pub fn clear(&mut self) {
+        self.paths.clear();
+    }
+
+    fn key(&self, map: Entity, radius: f32, from: Vec2, to: Vec2) -> PathCacheKey {
+        let cell = |pos: Vec2| (pos / self.cell_size).floor().as_ivec2();
+        (map, radius.to_bits(), cell(from), cell(to))
+    }
+
+    fn get(&self, map: Entity, radius: f32, from: Vec2, to: Vec2) -> Option<VecDeque<Vec2>> {
+        let mut path = self.paths.get(&self.key(map, radius, from, to))?.clone();
+        *path.front_mut()? = from;
+        *path.back_mut()? = to;
+        Some(path)
+    }
+
+    fn insert(&mut self, map: Entity, radius: f32, from: Vec2, to: Vec2, path: &VecDeque<Vec2>) {
+        if path.len() < 2 || self.capacity == 0 {
+            return;
+        }
+        if self.paths.len() >= self.capacity {
+            self.paths.clear();
+        }
+        self.paths
+            .insert(self.key(map, radius, from, to), path.clone());
+    }
+
+    fn invalidate(&mut self, map: Entity) {
+        self.paths.retain(|&(path_map, ..), _| path_map != map);
     }
 }
 
@@ -93,64 +967,502 @@ pub struct NavBundle {
     pub nav: Nav,
 }
 
+/// Extension trait for giving many entities the same [`NavBundle`]. Import it from the prelude to
+/// use it on [`Commands`].
+pub trait NavigateManyExt {
+    /// Inserts a clone of `bundle`, with its path state cleared, on each of the `entities` that
+    /// still exists
+    fn navigate_many(&mut self, entities: &[Entity], bundle: NavBundle);
+}
+
+impl NavigateManyExt for Commands<'_, '_> {
+    fn navigate_many(&mut self, entities: &[Entity], mut bundle: NavBundle) {
+        bundle.pathfind.path = VecDeque::new();
+        bundle.pathfind.path_radius = None;
+        bundle.pathfind.path_map = None;
+        bundle.pathfind.path_status = None;
+        bundle.pathfind.last_waypoint = None;
+        bundle.pathfind.priority_index = None;
+        bundle.pathfind.stopped = false;
+        bundle.pathfind.has_pathed = false;
+        bundle.pathfind.force_repath = false;
+        bundle.nav.done = false;
+        bundle.nav.velocity = Vec2::ZERO;
+
+        let entities = entities.to_vec();
+        self.add(move |world: &mut World| {
+            for entity in entities {
+                if let Some(mut entity) = world.get_entity_mut(entity) {
+                    entity.insert(bundle.clone());
+                }
+            }
+        });
+    }
+}
+
+fn stop_on_removed_maps(
+    #[cfg(feature = "state")] mut commands: Commands,
+    mut removed: RemovedComponents<Navmeshes>,
+    maps: Query<(), With<Navmeshes>>,
+    mut pathfinds: Query<(Entity, &mut Pathfind, Option<&mut Nav>)>,
+    mut failures: EventWriter<NavFailed>,
+    enabled: Option<Res<NavEnabled>>,
+    // Maps removed while navigation was paused, since removals are only kept for a frame
+    mut pending: Local<HashSet<Entity>>,
+) {
+    pending.extend(removed.iter());
+    if !nav_enabled(enabled) {
+        return;
+    }
+
+    let removed = pending
+        .drain()
+        .filter(|&map| !maps.contains(map))
+        .collect::<HashSet<_>>();
+    if removed.is_empty() {
+        return;
+    }
+
+    for (entity, mut pathfind, nav) in &mut pathfinds {
+        if !removed.contains(&pathfind.map) {
+            continue;
+        }
+
+        #[cfg(feature = "log")]
+        warn!(
+            "navmeshes of map {:?} of {entity:?} were removed; stopping navigation",
+            pathfind.map
+        );
+
+        failures.send(NavFailed {
+            entity,
+            reason: NavFailReason::MissingNavmesh,
+        });
+        pathfind.path.clear();
+        pathfind.path_status = None;
+        pathfind.next_repath = Duration::MAX;
+
+        if let Some(mut nav) = nav {
+            nav.done = true;
+        }
+
+        #[cfg(feature = "state")]
+        commands.entity(entity).insert(Done::Failure);
+    }
+}
+
+fn share_paths(
+    #[cfg(feature = "state")] mut commands: Commands,
+    mut followers: Query<(Entity, &mut SharedPath, &mut Pathfind, Option<&mut Nav>)>,
+    leaders: Query<&Pathfind, Without<SharedPath>>,
+    meshes: Query<&Navmeshes>,
+    map_changes: MapChanges,
+    mut failures: EventWriter<NavFailed>,
+) {
+    let changed_maps = map_changes.get();
+
+    for (entity, mut shared, mut pathfind, nav) in &mut followers {
+        let Ok(leader) = leaders.get(shared.leader) else {
+            if shared.copied.is_some() || !pathfind.has_pathed {
+                #[cfg(feature = "log")]
+                warn!(
+                    "leader {:?} of {entity:?} has no path to share; stopping navigation",
+                    shared.leader
+                );
+
+                failures.send(NavFailed {
+                    entity,
+                    reason: NavFailReason::MissingTarget,
+                });
+                shared.copied = None;
+                pathfind.has_pathed = true;
+                pathfind.path.clear();
+                pathfind.path_status = None;
+
+                if let Some(mut nav) = nav {
+                    nav.done = true;
+                }
+
+                #[cfg(feature = "state")]
+                commands.entity(entity).insert(Done::Failure);
+            }
+            continue;
+        };
+
+        // The leader popping waypoints shortens its path without changing its destination,
+        // and the follower pops its own copy as it goes. A map change may reroute the leader
+        // without changing either, and may leave the follower waiting for a new path.
+        let Some(&destination) = leader.path.back() else { continue };
+        let new_path = !pathfind.has_pathed
+            || pathfind.force_repath
+            || changed_maps.contains(&leader.map)
+            || match shared.copied {
+                Some((len, copied_destination)) => {
+                    leader.path.len() > len || destination != copied_destination
+                }
+                None => true,
+            };
+        shared.copied = Some((leader.path.len(), destination));
+        if !new_path {
+            continue;
+        }
+
+        let navmeshes = meshes.get(pathfind.map).ok();
+        let offset = shared.offset;
+        let radius = pathfind.radius;
+        pathfind.path = leader
+            .path
+            .iter()
+            .map(|&waypoint| {
+                let waypoint = waypoint + offset;
+                navmeshes
+                    .and_then(|navmeshes| navmeshes.nearest_point(radius, waypoint))
+                    .unwrap_or(waypoint)
+            })
+            .collect();
+        pathfind.path_status = leader.path_status;
+        pathfind.path_radius = Some(radius);
+        pathfind.has_pathed = true;
+        pathfind.force_repath = false;
+        pathfind.last_waypoint = None;
+
+        if let Some(mut nav) = nav {
+            nav.done = false;
+        }
+    }
+}
+
+fn advance_target_queues(mut queues: Query<(&mut TargetQueue, &mut Pathfind)>) {
+    for (mut queue, mut pathfind) in &mut queues {
+        let arrived = pathfind.done() && queue.targets.front() == Some(&pathfind.target);
+        if arrived {
+            queue.targets.pop_front();
+        }
+
+        let Some(target) = queue.targets.front() else { continue };
+        if arrived || *target != pathfind.target {
+            pathfind.set_target(target.clone());
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn generate_paths<P: Position2<Position = Vec2>>(
     #[cfg(feature = "state")] mut commands: Commands,
     positions: Query<&P>,
-    mut pathfinds: Query<(Entity, &P, &mut Pathfind)>,
+    mut pathfinds: Query<(Entity, &P, &mut Pathfind), Without<SharedPath>>,
     mut navs: Query<&mut Nav>,
-    meshes: Query<&Navmeshes>,
+    meshes: Query<Ref<Navmeshes>>,
     time: Res<Time>,
+    time_override: Option<Res<NavTimeOverride>>,
+    mut starts: EventWriter<NavStarted>,
+    mut arrivals: EventWriter<NavArrived>,
+    mut failures: EventWriter<NavFailed>,
+    mut cache: Option<ResMut<PathCache>>,
+    post_processor: Option<Res<PathPostProcessor>>,
+    map_changes: MapChanges,
+    #[cfg(feature = "diagnostics")] mut diagnostics: Diagnostics,
+    // Entities that have been warned about repathing every frame
+    #[cfg(feature = "log")] mut warned: Local<HashSet<Entity>>,
 ) {
+    let changed_maps = map_changes.get();
+
+    #[cfg(feature = "diagnostics")]
+    let (mut repaths, mut paths, mut total_length) = (0, 0, 0.);
+
+    if let Some(cache) = &mut cache {
+        for &map in &changed_maps {
+            cache.invalidate(map);
+        }
+    }
+
+    #[cfg(any(feature = "log", feature = "diagnostics"))]
+    let delta = time_override
+        .as_ref()
+        .map_or(time.delta(), |time_override| time_override.delta);
+    let elapsed = time_override.map_or(time.elapsed(), |time_override| time_override.elapsed);
+
+    // For entities that surround a `PathTarget::Dynamic`, their index among the entities
+    // surrounding the same target, and how many there are
+    let mut surrounders = HashMap::<_, Vec<_>>::default();
+    for (entity, _, pathfind) in &pathfinds {
+        if let PathTarget::Dynamic(target) = pathfind.target {
+            if pathfind.surround_radius > 0. {
+                surrounders.entry(target).or_default().push(entity);
+            }
+        }
+    }
+    let surround_slots = surrounders
+        .into_values()
+        .flat_map(|mut entities| {
+            entities.sort();
+            let count = entities.len();
+            entities
+                .into_iter()
+                .enumerate()
+                .map(move |(index, entity)| (entity, (index, count)))
+        })
+        .collect::<HashMap<_, _>>();
+
     #[allow(unused_variables)]
     for (entity, position, mut pathfind) in &mut pathfinds {
-        let repath = pathfind
-            .repath_frequency
-            .map(|repath_frequency| {
-                let repath = pathfind.next_repath <= time.elapsed();
-                if repath {
-                    pathfind.next_repath = time.elapsed() + repath_frequency;
-                }
-                repath
-            })
-            .unwrap_or_else(|| {
-                let path = pathfind.next_repath == Duration::ZERO;
-                if path {
-                    pathfind.next_repath = Duration::MAX;
+        if pathfind.stopped {
+            continue;
+        }
+
+        let left_area = match pathfind.target {
+            PathTarget::Area { center, radius } => {
+                pathfind.path.is_empty() && position.get().distance(center) > radius
+            }
+            _ => false,
+        };
+
+        // Arrival at a moving target is checked against where it is now, and holds off repathing
+        // until it moves out of range
+        if let PathTarget::Dynamic(target) = pathfind.target {
+            let in_range = pathfind.has_pathed
+                && pathfind.done_distance > 0.
+                && positions.get(target).is_ok_and(|target_pos| {
+                    target_pos.get().distance(position.get()) <= pathfind.done_distance
+                });
+
+            if in_range {
+                if !pathfind.path.is_empty() {
+                    arrivals.send(NavArrived { entity, target });
+                    pathfind.path.clear();
+
+                    if let Ok(mut nav) = navs.get_mut(entity) {
+                        nav.done = true;
+                    }
+
+                    #[cfg(feature = "state")]
+                    commands.entity(entity).insert(Done::Success);
+                }
+                continue;
+            }
+        }
+
+        let map_switched = pathfind
+            .path_map
+            .is_some_and(|path_map| path_map != pathfind.map);
+        let repath = !pathfind.has_pathed
+            || pathfind.force_repath
+            || left_area
+            || map_switched
+            || pathfind.repath_frequency.is_some() && pathfind.next_repath <= elapsed;
+
+        if repath {
+            if let Some(repath_frequency) = pathfind.repath_frequency {
+                #[cfg(feature = "log")]
+                if repath_frequency < delta && warned.insert(entity) {
+                    warn!(
+                        "{entity:?} has a repath frequency of {repath_frequency:?}, which is \
+                        shorter than a frame, so it will repath every frame"
+                    );
                 }
-                path
+
+                let jitter = match pathfind.has_pathed {
+                    true => 0.,
+                    false => pathfind.repath_jitter.clamp(0., 1.) * jitter_fraction(entity),
+                };
+                pathfind.next_repath = elapsed + repath_frequency.mul_f32(1. - jitter);
+            }
+            pathfind.has_pathed = true;
+            pathfind.force_repath = false;
+        }
+
+        let map_changed = changed_maps.contains(&pathfind.map);
+        let radius_changed = pathfind
+            .path_radius
+            .is_some_and(|path_radius| path_radius != pathfind.radius);
+
+        if !repath && !map_changed && !radius_changed {
+            continue;
+        }
+
+        if !position.get().is_finite() {
+            #[cfg(feature = "log")]
+            warn!(
+                "{entity:?} has an invalid position of {}; not generating a path",
+                position.get()
+            );
+
+            failures.send(NavFailed {
+                entity,
+                reason: NavFailReason::InvalidPosition,
             });
+            pathfind.path.clear();
+            pathfind.path_status = None;
+
+            if let Ok(mut nav) = navs.get_mut(entity) {
+                nav.done = true;
+            }
+
+            #[cfg(feature = "state")]
+            commands.entity(entity).insert(Done::Failure);
 
-        if !repath {
             continue;
         }
 
-        let path = || -> Result<VecDeque<Vec2>, Box<dyn Error>> {
-            Ok(meshes
-                .get(pathfind.map)?
-                .mesh(pathfind.radius)
-                .ok_or_else(|| {
-                    format!(
-                        "missing navmesh with clearance of at least {}",
-                        pathfind.radius
-                    )
-                })?
-                .find_path(
-                    Vector3::from(position.get().extend(0.)).into(),
-                    Vector3::from(
-                        match pathfind.target {
-                            PathTarget::Static(target) => target,
-                            PathTarget::Dynamic(target) => positions.get(target)?.get(),
-                        }
-                        .extend(0.),
-                    )
-                    .into(),
+        // Only report a missing clearance once, instead of on every repath
+        let clearance_changed = pathfind.path_radius != Some(pathfind.radius)
+            || pathfind.path_map != Some(pathfind.map);
+        pathfind.path_radius = Some(pathfind.radius);
+        pathfind.path_map = Some(pathfind.map);
+        if clearance_changed
+            && meshes
+                .get(pathfind.map)
+                .is_ok_and(|navmeshes| navmeshes.mesh(pathfind.radius).is_none())
+        {
+            failures.send(NavFailed {
+                entity,
+                reason: NavFailReason::MissingClearance,
+            });
+        }
+
+        let map = meshes.get(pathfind.map).ok();
+        let map = map.as_deref().map(|navmeshes| (navmeshes, pathfind.radius));
+        let target = target_position(&pathfind.target, position.get(), &positions, &navs, map).map(
+            |target| match surround_slots.get(&entity) {
+                Some(&(index, count)) => {
+                    target
+                        + Vec2::from_angle(TAU * index as f32 / count as f32)
+                            * pathfind.surround_radius
+                }
+                None => target,
+            },
+        );
+
+        if let Err(QueryEntityError::NoSuchEntity(missing)) = target {
+            #[cfg(feature = "log")]
+            warn!("target {missing:?} of {entity:?} was despawned; stopping navigation");
+
+            failures.send(NavFailed {
+                entity,
+                reason: NavFailReason::MissingTarget,
+            });
+            pathfind.path.clear();
+            pathfind.path_status = None;
+            pathfind.stopped = true;
+
+            if let Ok(mut nav) = navs.get_mut(entity) {
+                nav.done = true;
+            }
+
+            #[cfg(feature = "state")]
+            commands.entity(entity).insert(Done::Failure);
+
+            continue;
+        }
+
+        #[allow(clippy::type_complexity)]
+        let path = || -> Result<(VecDeque<Vec2>, PathStatus, Option<usize>), Box<dyn Error>> {
+            let navmeshes = meshes.get(pathfind.map)?;
+            navmeshes.mesh(pathfind.radius).ok_or_else(|| {
+                format!(
+                    "missing navmesh with clearance of at least {}",
+                    pathfind.radius
+                )
+            })?;
+            let start = match pathfind.snap_start {
+                true => navmeshes
+                    .nearest_point(pathfind.radius, position.get())
+                    .unwrap_or(position.get()),
+                false => position.get(),
+            };
+            let find_path = |target: Vec2| {
+                let path = navmeshes.find_path(
+                    pathfind.radius,
+                    start,
+                    target,
                     pathfind.query,
                     pathfind.path_mode,
-                )
-                .ok_or("no valid path was found")?
-                .into_iter()
-                .map(|pos| Vec3::from(Vector3::from(pos)).truncate())
-                .collect())
+                )?;
+                let status = match path
+                    .last()
+                    .is_some_and(|last| last.distance(target) <= ON_MESH_TOLERANCE)
+                {
+                    true => PathStatus::Complete,
+                    false => PathStatus::Partial,
+                };
+                Some((VecDeque::from(path), status))
+            };
+
+            Ok(match &pathfind.target {
+                PathTarget::Priority(targets) => targets
+                    .iter()
+                    .enumerate()
+                    .find_map(|(index, target)| {
+                        let target =
+                            target_position(target, position.get(), &positions, &navs, map).ok()?;
+                        let (path, status) = find_path(target)?;
+                        Some((path, status, Some(index)))
+                    })
+                    .ok_or("none of the prioritized targets have a valid path")?,
+                PathTarget::Orbit {
+                    center,
+                    radius,
+                    clockwise,
+                } => {
+                    let (path, status) = (1..=ORBIT_ATTEMPTS)
+                        .find_map(|attempt| {
+                            find_path(orbit_point(
+                                *center,
+                                *radius,
+                                *clockwise,
+                                position.get(),
+                                attempt as f32 * ORBIT_LEAD,
+                            ))
+                        })
+                        .ok_or("no point on the orbit has a valid path")?;
+                    (path, status, None)
+                }
+                PathTarget::Area { center, radius } => {
+                    match position.get().distance(*center) <= *radius {
+                        true => (VecDeque::new(), PathStatus::Complete, None),
+                        false => {
+                            let (path, status) =
+                                find_path(*center).ok_or("no valid path was found")?;
+                            (path, status, None)
+                        }
+                    }
+                }
+                _ => {
+                    let target = target?;
+                    if let Some(path) = cache
+                        .as_deref()
+                        .and_then(|cache| cache.get(pathfind.map, pathfind.radius, start, target))
+                    {
+                        return Ok((path, PathStatus::Complete, None));
+                    }
+
+                    let found = find_path(target);
+                    if let (Some(cache), Some((path, PathStatus::Complete))) =
+                        (cache.as_deref_mut(), &found)
+                    {
+                        cache.insert(pathfind.map, pathfind.radius, start, target, path);
+                    }
+
+                    let (path, status) = found
+                        .or_else(|| {
+                            let fallback = pathfind.nearest_fallback
+                                && matches!(pathfind.target, PathTarget::Dynamic(_));
+                            if !fallback {
+                                return None;
+                            }
+
+                            let nearest = navmeshes.nearest_reachable_point(
+                                pathfind.radius,
+                                start,
+                                target,
+                            )?;
+                            let (path, _) = find_path(nearest)?;
+                            Some((path, PathStatus::Partial))
+                        })
+                        .ok_or("no valid path was found")?;
+                    (path, status, None)
+                }
+            })
         }();
 
         #[cfg(feature = "log")]
@@ -159,7 +1471,51 @@ pub(crate) fn generate_paths<P: Position2<Position = Vec2>>(
         }
         #[cfg(feature = "state")]
         let failure = path.is_err();
-        pathfind.path = path.unwrap_or_default();
+        let was_empty = pathfind.path.is_empty();
+        (pathfind.path, pathfind.path_status, pathfind.priority_index) = match path {
+            Ok((path, status, priority_index)) => (path, Some(status), priority_index),
+            Err(_) => default(),
+        };
+
+        // The path starts at the start snapped onto the navmesh, which may be behind an entity
+        // that's moving when it repaths, or beside one that was pushed slightly off of the navmesh.
+        // Skip it if the entity is already past it or within its clearance radius of it,
+        // so the entity doesn't step back or sideways every repath.
+        if let [first, second, ..] = *pathfind.path.make_contiguous() {
+            if (position.get() - first).dot(second - first) >= 0.
+                || position.get().distance(first) <= pathfind.radius
+            {
+                pathfind.path.pop_front();
+            }
+        }
+        if let Some(PathPostProcessor(post_processor)) = post_processor.as_deref() {
+            if !pathfind.path.is_empty() {
+                post_processor(entity, &mut pathfind.path);
+            }
+        }
+        pathfind.last_waypoint = Some(position.get());
+
+        #[cfg(feature = "diagnostics")]
+        {
+            repaths += 1;
+            if !pathfind.path.is_empty() {
+                paths += 1;
+                total_length += pathfind
+                    .path
+                    .iter()
+                    .fold((position.get(), 0.), |(previous, length), &waypoint| {
+                        (waypoint, length + previous.distance(waypoint) as f64)
+                    })
+                    .1;
+            }
+        }
+
+        if was_empty && !pathfind.path.is_empty() {
+            starts.send(NavStarted {
+                entity,
+                path_len: pathfind.path.len(),
+            });
+        }
 
         let Ok(mut nav) = navs.get_mut(entity) else { continue };
 
@@ -170,49 +1526,551 @@ pub(crate) fn generate_paths<P: Position2<Position = Vec2>>(
             commands.entity(entity).insert(Done::Failure);
         }
     }
+
+    #[cfg(feature = "diagnostics")]
+    {
+        diagnostics.add_measurement(MapNavDiagnostics::PATHFINDERS, || {
+            pathfinds.iter().len() as f64
+        });
+        if delta > Duration::ZERO {
+            diagnostics.add_measurement(MapNavDiagnostics::REPATHS, || {
+                repaths as f64 / delta.as_secs_f64()
+            });
+        }
+        if paths > 0 {
+            diagnostics.add_measurement(MapNavDiagnostics::PATH_LENGTH, || {
+                total_length / paths as f64
+            });
+        }
+    }
+}
+
+// Angle ahead of the navigator to target when orbiting, and how many multiples of it to try
+const ORBIT_LEAD: f32 = FRAC_PI_4;
+const ORBIT_ATTEMPTS: u32 = 7;
+
+fn orbit_point(center: Vec2, radius: f32, clockwise: bool, from: Vec2, lead: f32) -> Vec2 {
+    let offset = from - center;
+    let lead = match clockwise {
+        true => -lead,
+        false => lead,
+    };
+    center + Vec2::from_angle(offset.y.atan2(offset.x) + lead) * radius
+}
+
+// `navmeshes` is the navigator's map and clearance radius, if known, to find boundaries on
+fn target_position<P: Position2<Position = Vec2>>(
+    target: &PathTarget,
+    from: Vec2,
+    positions: &Query<&P>,
+    navs: &Query<&mut Nav>,
+    navmeshes: Option<(&Navmeshes, f32)>,
+) -> Result<Vec2, QueryEntityError> {
+    Ok(match *target {
+        PathTarget::Static(target) => target,
+        PathTarget::Dynamic(target) => positions.get(target)?.get(),
+        PathTarget::FormationSlot { leader, offset } => {
+            let heading = navs
+                .get(leader)
+                .ok()
+                .and_then(|nav| nav.velocity.try_normalize())
+                .unwrap_or(Vec2::X);
+            positions.get(leader)?.get() + heading.rotate(offset)
+        }
+        PathTarget::Priority(ref targets) => {
+            let mut targets = targets
+                .iter()
+                .map(|target| target_position(target, from, positions, navs, navmeshes));
+            let first = targets
+                .next()
+                .unwrap_or(Err(QueryEntityError::NoSuchEntity(Entity::PLACEHOLDER)));
+            targets.fold(first, Result::or)?
+        }
+        PathTarget::Orbit {
+            center,
+            radius,
+            clockwise,
+        } => orbit_point(center, radius, clockwise, from, ORBIT_LEAD),
+        PathTarget::NearestBoundary => navmeshes
+            .and_then(|(navmeshes, radius)| navmeshes.nearest_boundary(radius, from))
+            .unwrap_or(from),
+        PathTarget::Area { center, .. } => center,
+        PathTarget::Interpose {
+            protect,
+            from: threat,
+            offset,
+        } => {
+            let protect = positions.get(protect)?.get();
+            match positions.get(threat) {
+                Ok(threat) => {
+                    let threat = threat.get();
+                    protect
+                        + (threat - protect).normalize_or_zero()
+                            * offset.min(protect.distance(threat) / 2.)
+                }
+                Err(_) => protect,
+            }
+        }
+        PathTarget::Blend { ref a, ref b, t } => {
+            target_position(a, from, positions, navs, navmeshes)?
+                .lerp(target_position(b, from, positions, navs, navmeshes)?, t)
+        }
+    })
+}
+
+fn update_motion_states(mut states: Query<(&Nav, &mut NavMotionState)>) {
+    for (nav, mut state) in &mut states {
+        let new_state = match nav.velocity == Vec2::ZERO || nav.speed <= 0. {
+            true => NavMotionState::Idle,
+            false => NavMotionState::Moving(nav.velocity.length() / nav.speed),
+        };
+
+        if *state != new_state {
+            *state = new_state;
+        }
+    }
 }
 
+fn record_trails<P: Position2<Position = Vec2>>(
+    mut trails: Query<(&P, Option<&NavOutput>, &mut NavTrail)>,
+    nav_time: NavTime,
+) {
+    let delta = Duration::from_secs_f32(nav_time.delta_seconds());
+
+    for (position, output, mut trail) in &mut trails {
+        trail.since_sample += delta;
+        if !trail.points.is_empty() && trail.since_sample < trail.interval {
+            continue;
+        }
+
+        trail.since_sample = Duration::ZERO;
+        let position = output.map_or(position.get(), |output| output.position);
+        trail.points.push_back(position);
+        while trail.points.len() > trail.max_len {
+            trail.points.pop_front();
+        }
+    }
+}
+
+fn face_targets<P: Position2<Position = Vec2>>(
+    mut faces: Query<(&P, &FaceTarget, &mut Facing)>,
+    positions: Query<&P>,
+    navs: Query<&mut Nav>,
+) {
+    for (position, FaceTarget(target), mut facing) in &mut faces {
+        let position = position.get();
+        let Ok(target) = target_position(target, position, &positions, &navs, None) else {
+            continue;
+        };
+        let offset = target - position;
+
+        if offset != Vec2::ZERO {
+            facing.0 = offset.y.atan2(offset.x);
+        }
+    }
+}
+
+fn face_movement(mut faces: Query<(&Nav, &mut Facing), Without<FaceTarget>>) {
+    for (nav, mut facing) in &mut faces {
+        if nav.velocity == Vec2::ZERO {
+            continue;
+        }
+
+        let heading = Vec2::from_angle(facing.0);
+        let direction = nav.velocity.normalize();
+        if nav.reverse_angle > 0. && (-heading).angle_between(direction).abs() <= nav.reverse_angle
+        {
+            continue;
+        }
+
+        facing.0 = direction.y.atan2(direction.x);
+    }
+}
+
+// How many times `clamp_to_navmesh` halves the interval it searches
+const CLAMP_ITERATIONS: u32 = 8;
+
+// Gets the furthest point on the segment from `from` to `to` that a navigator can move to
+// in a straight line without leaving the navmesh, so fast navigators can't pass through
+// thin obstacles. Navigators that are already off of the navmesh aren't clamped.
+fn clamp_to_navmesh(navmeshes: &Navmeshes, clearance: f32, from: Vec2, to: Vec2) -> Vec2 {
+    if navmeshes.line_of_sight(clearance, from, to) || !navmeshes.is_navigable(clearance, from) {
+        return to;
+    }
+
+    let (mut reachable, mut blocked) = (0., 1.);
+    for _ in 0..CLAMP_ITERATIONS {
+        let middle = (reachable + blocked) / 2.;
+        match navmeshes.line_of_sight(clearance, from, from.lerp(to, middle)) {
+            true => reachable = middle,
+            false => blocked = middle,
+        }
+    }
+
+    from.lerp(to, reachable)
+}
+
+fn group_arrivals(
+    navs: Query<(&NavGroup, &Nav)>,
+    mut arrivals: EventWriter<NavGroupArrived>,
+    // Groups whose members had all arrived last time this ran
+    mut arrived: Local<HashSet<NavGroup>>,
+) {
+    let mut groups = HashMap::<_, bool>::default();
+    for (&group, nav) in &navs {
+        *groups.entry(group).or_insert(true) &= nav.done;
+    }
+
+    let now_arrived = groups
+        .into_iter()
+        .filter_map(|(group, done)| done.then_some(group))
+        .collect::<HashSet<_>>();
+    for &group in now_arrived.difference(&arrived) {
+        arrivals.send(NavGroupArrived { group });
+    }
+
+    *arrived = now_arrived;
+}
+
+// Deterministic value in `[0, 1)` derived from the entity, so entities spawned together
+// get different repath offsets
+fn jitter_fraction(entity: Entity) -> f32 {
+    let mut bits = entity.to_bits();
+    bits ^= bits >> 33;
+    bits = bits.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    bits ^= bits >> 33;
+    (bits >> 40) as f32 / (1 << 24) as f32
+}
+
+// How many consecutive frames a navigator must be halted behind another before `NavBlocked`
+// is sent
+const BLOCKED_FRAMES: u32 = 5;
+
+fn nav_enabled(enabled: Option<Res<NavEnabled>>) -> bool {
+    enabled.is_none_or(|enabled| enabled.0)
+}
+
+// Maps whose `Navmeshes` changed since the system last ran. Systems don't run while `NavEnabled`
+// is `false`, and change detection compares against their last run, so this includes changes
+// made during a pause.
+#[derive(SystemParam)]
+pub(crate) struct MapChanges<'w, 's> {
+    changed: Query<'w, 's, Entity, Changed<Navmeshes>>,
+}
+
+impl MapChanges<'_, '_> {
+    fn get(&self) -> HashSet<Entity> {
+        self.changed.iter().collect()
+    }
+}
+
+// Time that navigators move by each time navigation runs
+#[derive(SystemParam)]
+struct NavTime<'w> {
+    time: Res<'w, Time>,
+    fixed_time: Option<Res<'w, FixedTime>>,
+    fixed_nav: Option<Res<'w, FixedNav>>,
+    time_override: Option<Res<'w, NavTimeOverride>>,
+}
+
+impl NavTime<'_> {
+    fn delta_seconds(&self) -> f32 {
+        match (&self.time_override, &self.fixed_time) {
+            (Some(time_override), _) => time_override.delta.as_secs_f32(),
+            (None, Some(fixed_time)) if self.fixed_nav.is_some() => fixed_time.period.as_secs_f32(),
+            _ => self.time.delta_seconds(),
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
 fn nav<P: Position2<Position = Vec2>>(
     #[cfg(feature = "state")] mut commands: Commands,
-    mut navs: Query<(Entity, &mut P, &mut Pathfind, &mut Nav)>,
-    time: Res<Time>,
+    mut navs: Query<(
+        Entity,
+        &mut P,
+        &mut Pathfind,
+        &mut Nav,
+        Option<&VelocityOverride>,
+        Option<&mut NavOutput>,
+        Option<&mut NavHistory>,
+    )>,
+    meshes: Query<&Navmeshes>,
+    map_changes: MapChanges,
+    nav_time: NavTime,
+    mut blocks: EventWriter<NavBlocked>,
+    // Frames that each navigator has been halted behind another, by the pair of them
+    mut blocked: Local<HashMap<(Entity, Entity), u32>>,
 ) {
+    let changed_maps = map_changes.get();
+
+    let mut still_blocked = HashMap::default();
+    let delta_seconds = nav_time.delta_seconds();
+
+    // Positions, clearance radii, headings and push priorities of navigators that haven't
+    // arrived, for spacing. Stopped navigators head toward their next waypoint, so two that
+    // stopped head-on don't wait for each other.
+    let others = match navs.iter().any(|(_, _, _, nav, ..)| nav.spacing > 0.) {
+        true => navs
+            .iter()
+            .filter(|(_, _, pathfind, ..)| !pathfind.path.is_empty())
+            .map(|(entity, position, pathfind, nav, ..)| {
+                let heading = match nav.velocity == Vec2::ZERO {
+                    true => pathfind.path[0] - position.get(),
+                    false => nav.velocity,
+                };
+                (
+                    entity,
+                    position.get(),
+                    pathfind.radius,
+                    heading,
+                    nav.push_priority,
+                )
+            })
+            .collect(),
+        false => Vec::default(),
+    };
+
     #[allow(unused_variables)]
-    for (entity, mut position, mut pathfind, mut nav) in &mut navs {
-        if pathfind.path.is_empty() {
+    for (entity, mut position, mut pathfind, mut nav, velocity_override, mut output, mut history) in
+        &mut navs
+    {
+        let start = position.get();
+
+        if nav.recovery_speed > 0. {
+            let nearest = meshes
+                .get(pathfind.map)
+                .ok()
+                .and_then(|navmeshes| navmeshes.nearest_point(pathfind.radius, start));
+
+            if let Some(nearest) =
+                nearest.filter(|nearest| nearest.distance(start) > ON_MESH_TOLERANCE)
+            {
+                let pos = start.lerp(
+                    nearest,
+                    (nav.recovery_speed * delta_seconds / nearest.distance(start)).min(1.),
+                );
+                nav.velocity = match delta_seconds > 0. {
+                    true => (pos - start) / delta_seconds,
+                    false => Vec2::ZERO,
+                };
+
+                match output {
+                    Some(mut output) => {
+                        output.position = pos;
+                        output.velocity = nav.velocity;
+                    }
+                    None => position.set(pos),
+                }
+                continue;
+            }
+        }
+
+        if let PathTarget::Area { center, radius } = pathfind.target {
+            if start.distance(center) <= radius {
+                pathfind.path.clear();
+            }
+        }
+
+        let arrived = pathfind.path.is_empty();
+        if arrived || nav.speed <= 0. {
+            #[cfg(feature = "log")]
+            if !arrived && nav.is_changed() {
+                warn!(
+                    "{entity:?} has a speed of {}, so it will not make progress",
+                    nav.speed
+                );
+            }
+
+            nav.velocity = match arrived && nav.arrival_deceleration > 0. {
+                true => nav.velocity.clamp_length_max(
+                    (nav.velocity.length() - nav.arrival_deceleration * delta_seconds).max(0.),
+                ),
+                false => Vec2::ZERO,
+            };
+            let mut pos = start + nav.velocity * delta_seconds;
+            if pos != start {
+                if let Ok(navmeshes) = meshes.get(pathfind.map) {
+                    pos = clamp_to_navmesh(navmeshes, pathfind.radius, start, pos);
+                    if delta_seconds > 0. {
+                        nav.velocity = (pos - start) / delta_seconds;
+                    }
+                }
+            }
+
+            match &mut output {
+                Some(output) => {
+                    output.position = pos;
+                    output.velocity = nav.velocity;
+                }
+                None if pos != start => position.set(pos),
+                None => (),
+            }
             #[cfg(feature = "state")]
-            commands.entity(entity).insert(Done::Success);
+            if arrived {
+                commands.entity(entity).insert(Done::Success);
+            }
+            continue;
+        }
+
+        // If the map changed since the path was generated, the next waypoint may be in a wall now.
+        // If so, wait for a new path instead of heading into it.
+        if changed_maps.contains(&pathfind.map)
+            && meshes
+                .get(pathfind.map)
+                .is_ok_and(|navmeshes| !navmeshes.is_navigable(pathfind.radius, pathfind.path[0]))
+        {
+            pathfind.force_repath = true;
+            nav.velocity = Vec2::ZERO;
+            if let Some(output) = &mut output {
+                output.position = start;
+                output.velocity = Vec2::ZERO;
+            }
             continue;
         }
 
-        let mut pos = position.get();
-        let mut travel_dist = nav.speed * time.delta_seconds();
-        let mut dest;
-        let mut dest_dist;
+        let destination = *pathfind.path.back().unwrap();
+        if pathfind.path.len() > 1
+            && start.distance(destination) <= nav.seek_distance
+            && meshes
+                .get(pathfind.map)
+                .is_ok_and(|navmeshes| navmeshes.line_of_sight(pathfind.radius, start, destination))
+        {
+            let skipped = pathfind.path.len() - 1;
+            pathfind.path.drain(..skipped);
+        }
+
+        let mut pos = start;
+        let mut travel_dist = nav.speed.min(nav.max_speed) * delta_seconds;
+
+        if nav.spacing > 0. {
+            let direction = (pathfind.path[0] - start).normalize_or_zero();
+            let gap = others
+                .iter()
+                .filter(|&&(other, _, _, heading, push_priority)| {
+                    other != entity
+                        && heading.dot(direction) >= 0.
+                        && push_priority >= nav.push_priority
+                })
+                .filter_map(|&(other, other_pos, other_radius, ..)| {
+                    let offset = other_pos - start;
+                    let ahead = offset.dot(direction);
+                    (ahead > 0.
+                        && offset.perp_dot(direction).abs() < pathfind.radius + other_radius)
+                        .then_some((ahead, other))
+                })
+                .min_by(|(ahead, _), (other_ahead, _)| ahead.total_cmp(other_ahead));
+
+            if let Some((gap, by)) = gap {
+                travel_dist = travel_dist.min((gap - nav.spacing).max(0.));
+
+                if travel_dist == 0. {
+                    let frames = blocked.get(&(entity, by)).map_or(1, |frames| frames + 1);
+                    if frames == BLOCKED_FRAMES {
+                        blocks.send(NavBlocked { entity, by });
+                    }
+                    still_blocked.insert((entity, by), frames);
+                }
+            }
+        }
+        let mut reached = 0;
+
+        let last = pathfind.path.len() - 1;
+        for (index, &dest) in pathfind.path.iter().enumerate() {
+            let dest_dist = (dest - pos).length();
+            if index < last && dest_dist <= nav.corner_radius {
+                reached += 1;
+                continue;
+            }
+
+            if travel_dist < dest_dist {
+                pos += (dest - pos).normalize() * travel_dist;
+                break;
+            }
 
-        while travel_dist >= {
-            dest = *pathfind.path.front().unwrap();
-            dest_dist = (dest - pos).length();
-            dest_dist
-        } {
             pos = dest;
             travel_dist -= dest_dist;
+            reached += 1;
+        }
 
-            pathfind.path.pop_front();
-            if pathfind.path.is_empty() {
-                break;
+        let mut velocity = match delta_seconds > 0. {
+            true => (pos - start) / delta_seconds,
+            false => Vec2::ZERO,
+        };
+
+        let followed = velocity;
+        if let Some(VelocityOverride(velocity_override)) = velocity_override {
+            velocity = velocity_override(entity, velocity).clamp_length_max(nav.max_speed);
+        }
+        if nav.max_acceleration > 0. {
+            velocity = nav.velocity
+                + (velocity - nav.velocity).clamp_length_max(nav.max_acceleration * delta_seconds);
+        }
+
+        if velocity != followed {
+            pos = start + velocity * delta_seconds;
+
+            if let Some(last_waypoint) = pathfind
+                .last_waypoint
+                .filter(|_| nav.max_lateral_offset > 0.)
+            {
+                let segment = pathfind.path[0] - last_waypoint;
+                let along = match segment.length_squared() > 0. {
+                    true => ((pos - last_waypoint).dot(segment) / segment.length_squared())
+                        .clamp(0., 1.),
+                    false => 0.,
+                };
+                let closest = last_waypoint + segment * along;
+                pos = closest + (pos - closest).clamp_length_max(nav.max_lateral_offset);
             }
+
+            // Path following stays on the navmesh, but overridden or accelerated velocities may not
+            if let Ok(navmeshes) = meshes.get(pathfind.map) {
+                pos = clamp_to_navmesh(navmeshes, pathfind.radius, start, pos);
+                if delta_seconds > 0. {
+                    velocity = (pos - start) / delta_seconds;
+                }
+            }
+            let reach = velocity.length().max(nav.speed) * delta_seconds;
+            reached = pathfind
+                .path
+                .iter()
+                .take_while(|waypoint| waypoint.distance(pos) <= reach)
+                .count();
         }
 
-        if pathfind.path.is_empty() {
-            nav.done = true;
-            #[cfg(feature = "state")]
+        if reached > 0 {
+            pathfind.last_waypoint = Some(pathfind.path[reached - 1]);
+        }
+
+        match &mut history {
+            Some(history) => {
+                if history.waypoints.is_empty() {
+                    history.waypoints.push(start);
+                }
+                history.waypoints.extend(pathfind.path.drain(..reached));
+            }
+            None => {
+                pathfind.path.drain(..reached);
+            }
+        }
+
+        nav.velocity = velocity;
+        nav.done = pathfind.path.is_empty();
+
+        #[cfg(feature = "state")]
+        if nav.done {
             commands.entity(entity).insert(Done::Success);
-        } else {
-            let delta = (dest - pos).normalize() * travel_dist;
-            pos += delta;
         }
 
-        position.set(pos);
+        match output {
+            Some(mut output) => {
+                output.position = pos;
+                output.velocity = velocity;
+            }
+            None => position.set(pos),
+        }
     }
+
+    *blocked = still_blocked;
 }
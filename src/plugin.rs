@@ -1,27 +1,63 @@
 use std::marker::PhantomData;
 
-use crate::{nav::nav_plugin, prelude::*};
+#[cfg(feature = "diagnostics")]
+use crate::diagnostics::diagnostics_plugin;
+use crate::{nav::nav_plugin, prelude::*, task::task_plugin};
+use bevy::ecs::schedule::{BoxedScheduleLabel, ScheduleLabel};
 use seldom_fn_plugin::FnPluginExt;
 
 /// Add to your app to enable pathing and navigation. The type parameter accepts
 /// the position component used by your navigators.
+///
+/// To use your own position component, such as a networked position, implement
+/// `seldom_interop`'s `Position2` for it with `Position = Vec2`. `get` must return the entity's
+/// position in the same space as the map's [`Navmeshes`], and `set` is called with the position
+/// that the entity moves to, unless it has a [`NavOutput`]. Nothing else about the component
+/// is read. Entities referenced by a [`PathTarget`] must have the same position component.
 #[derive(Debug)]
-pub struct MapNavPlugin<P: Position2<Position = Vec2> = Transform>(PhantomData<P>);
+pub struct MapNavPlugin<P: Position2<Position = Vec2> = Transform> {
+    nav_schedule: BoxedScheduleLabel,
+    _position: PhantomData<P>,
+}
+
+impl<P: Position2<Position = Vec2>> MapNavPlugin<P> {
+    /// Set the schedule that moves navigators. Paths are still generated in `Update`.
+    /// Defaults to `Update`. In `FixedUpdate`, navigators move by [`FixedTime`]'s period
+    /// each step.
+    pub fn with_nav_schedule(mut self, schedule: impl ScheduleLabel) -> Self {
+        self.nav_schedule = Box::new(schedule);
+        self
+    }
+}
 
 impl<P: Position2<Position = Vec2>> Plugin for MapNavPlugin<P> {
     fn build(&self, app: &mut App) {
-        app.fn_plugin(map_nav_plugin::<P>);
+        configured_map_nav_plugin::<P>(app, self.nav_schedule.dyn_clone());
     }
 }
 
 impl<P: Position2<Position = Vec2>> Default for MapNavPlugin<P> {
     fn default() -> Self {
-        Self(default())
+        Self {
+            nav_schedule: Box::new(Update),
+            _position: default(),
+        }
     }
 }
 
-/// Function called by [`MapNavPlugin`]. You may instead call it directly
-/// or use `seldom_fn_plugin`, which is another crate I maintain.
+/// Function called by [`MapNavPlugin`], with the default configuration. You may instead call it
+/// directly or use `seldom_fn_plugin`, which is another crate I maintain.
 pub fn map_nav_plugin<P: Position2<Position = Vec2>>(app: &mut App) {
-    app.fn_plugin(nav_plugin::<P>);
+    configured_map_nav_plugin::<P>(app, Box::new(Update));
+}
+
+fn configured_map_nav_plugin<P: Position2<Position = Vec2>>(
+    app: &mut App,
+    nav_schedule: BoxedScheduleLabel,
+) {
+    app.fn_plugin(|app| nav_plugin::<P>(app, nav_schedule))
+        .fn_plugin(task_plugin);
+
+    #[cfg(feature = "diagnostics")]
+    app.fn_plugin(diagnostics_plugin);
 }
@@ -0,0 +1,79 @@
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use futures_lite::future::{block_on, poll_once};
+
+use crate::{mesh::NavmeshGenError, prelude::*, set::MapNavSet};
+
+pub(crate) fn task_plugin(app: &mut App) {
+    app.add_event::<NavmeshReady>()
+        .add_systems(Update, poll_navmesh_tasks.before(MapNavSet));
+}
+
+/// Add this component to your tilemap to generate its [`Navmeshes`] in the background.
+/// Create it with [`Navmeshes::generate_async`]. When generation finishes, it is replaced
+/// with the [`Navmeshes`] and a [`NavmeshReady`] is sent.
+#[derive(Component, Debug)]
+pub struct NavmeshTask(Task<Result<Navmeshes, NavmeshGenError>>);
+
+impl Navmeshes {
+    /// Like [`Navmeshes::generate`], but generates on the [`AsyncComputeTaskPool`], so large maps
+    /// don't stall the frame. Insert the returned [`NavmeshTask`] on your tilemap.
+    pub fn generate_async(
+        map_size: UVec2,
+        tile_size: Vec2,
+        navability: impl Fn(UVec2) -> Navability + Send + 'static,
+        clearances: impl IntoIterator<Item = f32>,
+    ) -> NavmeshTask {
+        let clearances = clearances.into_iter().collect::<Vec<_>>();
+        NavmeshTask(
+            AsyncComputeTaskPool::get()
+                .spawn(async move { Self::generate(map_size, tile_size, navability, clearances) }),
+        )
+    }
+
+    /// Like [`Navmeshes::set_region_navability`], but regenerates on the [`AsyncComputeTaskPool`],
+    /// so destructible maps don't stall the frame. Insert the returned [`NavmeshTask`]
+    /// on your tilemap. Navigators keep using the old navmeshes until the task finishes,
+    /// and repath once it does. The tiles are set right away, so if you make another change
+    /// before then, its task includes this one, and replaces this task.
+    pub fn set_region_navability_async(
+        &mut self,
+        center: Vec2,
+        radius: f32,
+        navable: bool,
+    ) -> NavmeshTask {
+        self.navability = self.region_navability(center, radius, navable);
+        let regenerate = self.regeneration(self.navability.clone());
+        NavmeshTask(AsyncComputeTaskPool::get().spawn(async move { regenerate() }))
+    }
+}
+
+/// Event sent when a [`NavmeshTask`] finishes and its [`Navmeshes`] are inserted
+#[derive(Clone, Copy, Debug, Event)]
+pub struct NavmeshReady {
+    /// The tilemap that the [`Navmeshes`] were inserted on
+    pub map: Entity,
+}
+
+fn poll_navmesh_tasks(
+    mut commands: Commands,
+    mut tasks: Query<(Entity, &mut NavmeshTask)>,
+    mut readies: EventWriter<NavmeshReady>,
+) {
+    for (map, mut task) in &mut tasks {
+        let Some(navmeshes) = block_on(poll_once(&mut task.0)) else { continue };
+        let mut map_commands = commands.entity(map);
+        map_commands.remove::<NavmeshTask>();
+
+        match navmeshes {
+            Ok(navmeshes) => {
+                map_commands.insert(navmeshes);
+                readies.send(NavmeshReady { map });
+            }
+            #[allow(unused_variables)]
+            Err(error) => {
+                #[cfg(feature = "log")]
+                warn!("failed to generate navmeshes for {map:?}: {error}");
+            }
+        }
+    }
+}
@@ -27,15 +27,29 @@ impl OrdinalAxis {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-pub(crate) enum Cardinal {
+/// A direction along one of the map's axes. North is toward positive y.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Cardinal {
+    /// Toward positive x
     East,
+    /// Toward positive y
     North,
+    /// Toward negative y
     South,
+    /// Toward negative x
     West,
 }
 
 impl Cardinal {
+    pub(crate) fn as_vec2(self) -> Vec2 {
+        match self {
+            Self::East => Vec2::X,
+            Self::North => Vec2::Y,
+            Self::South => Vec2::NEG_Y,
+            Self::West => Vec2::NEG_X,
+        }
+    }
+
     fn axis(self) -> CardinalAxis {
         match self {
             Self::North | Self::South => CardinalAxis::Vert,
@@ -0,0 +1,128 @@
+use glam::{UVec2, Vec2};
+use seldom_map_nav::{mesh::bordered, prelude::*};
+
+#[test]
+fn rectangular_tiles() {
+    const MAP_SIZE: UVec2 = UVec2::new(4, 4);
+    const TILE_SIZE: Vec2 = Vec2::new(2., 0.5);
+    const CLEARANCE: f32 = 0.1;
+
+    // One wall tile, which spans from (2, 0.5) to (4, 1) in world coordinates
+    let navmeshes = Navmeshes::generate(
+        MAP_SIZE,
+        TILE_SIZE,
+        |pos| match pos == UVec2::new(1, 1) {
+            true => Navability::Solid,
+            false => Navability::Navable,
+        },
+        [CLEARANCE],
+    )
+    .unwrap();
+    let (wall_min, wall_max) = (Vec2::new(2., 0.5), Vec2::new(4., 1.));
+
+    assert!(!navmeshes.is_navigable(CLEARANCE, (wall_min + wall_max) / 2.));
+    assert!(navmeshes.is_navigable(CLEARANCE, Vec2::new(1., 0.25)));
+    assert_eq!(
+        navmeshes.nearest_point(CLEARANCE, Vec2::new(3., 1.05)),
+        Some(Vec2::new(3., 1.1))
+    );
+
+    // Centers of the tiles at (0, 0) and (3, 3). The straight line between them crosses the wall.
+    let (from, to) = (Vec2::new(1., 0.25), Vec2::new(7., 1.75));
+    assert!(!navmeshes.line_of_sight(CLEARANCE, from, to));
+
+    let path = navmeshes
+        .find_path(CLEARANCE, from, to, DEFAULT_QUERY, DEFAULT_PATH_MODE)
+        .unwrap();
+    assert_eq!(path.first(), Some(&from));
+    assert_eq!(path.last(), Some(&to));
+
+    // The path turns at a corner of the wall, pushed out by the clearance
+    let corners = [
+        Vec2::new(wall_min.x - CLEARANCE, wall_max.y + CLEARANCE),
+        Vec2::new(wall_max.x + CLEARANCE, wall_min.y - CLEARANCE),
+    ];
+    let turns = &path[1..path.len() - 1];
+    assert!(!turns.is_empty());
+    for turn in turns {
+        assert!(
+            corners.iter().any(|corner| corner.distance(*turn) < 0.001),
+            "unexpected waypoint {turn} in {path:?}"
+        );
+    }
+}
+
+// A corridor from x = 1 to x = 11 and y = 1 to y = 4, with one-way tiles in the given columns
+fn one_way_corridor(strips: &[(u32, Cardinal)], clearances: &[f32]) -> Navmeshes {
+    const MAP_SIZE: UVec2 = UVec2::new(12, 5);
+
+    Navmeshes::generate(
+        MAP_SIZE,
+        Vec2::ONE,
+        bordered(MAP_SIZE, 1, |pos| {
+            strips
+                .iter()
+                .find(|&&(x, _)| x == pos.x)
+                .map_or(Navability::Navable, |&(_, direction)| {
+                    Navability::OneWay(direction)
+                })
+        }),
+        clearances.iter().copied(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn one_way_strip() {
+    const CLEARANCES: [f32; 3] = [0.1, 0.25, 0.4];
+
+    let navmeshes = one_way_corridor(&[(6, Cardinal::East)], &CLEARANCES);
+    let (west, east) = (Vec2::new(2.5, 2.5), Vec2::new(9.5, 2.5));
+
+    for clearance in CLEARANCES {
+        let path = navmeshes
+            .find_path(clearance, west, east, DEFAULT_QUERY, DEFAULT_PATH_MODE)
+            .unwrap();
+        assert_eq!(path.last(), Some(&east));
+        assert!(navmeshes
+            .find_path(clearance, east, west, DEFAULT_QUERY, DEFAULT_PATH_MODE)
+            .is_none());
+
+        assert!(navmeshes.path_cost(clearance, west, east).unwrap() >= west.distance(east));
+        assert_eq!(navmeshes.path_cost(clearance, east, west), None);
+
+        assert!(navmeshes.line_of_sight(clearance, west, east));
+        assert!(!navmeshes.line_of_sight(clearance, east, west));
+
+        // Stuck on the east side of the strip, which ends at x = 7
+        let reachable = navmeshes
+            .nearest_reachable_point(clearance, east, west)
+            .unwrap();
+        assert!(
+            (reachable.x - 7.).abs() < 0.001,
+            "nearest reachable point {reachable}"
+        );
+        assert_eq!(
+            navmeshes.nearest_reachable_point(clearance, west, east),
+            Some(east)
+        );
+    }
+}
+
+#[test]
+fn adjacent_one_way_strips() {
+    const CLEARANCE: f32 = 0.25;
+
+    // Moving east doesn't go against either strip. Moving west goes against the first.
+    let navmeshes = one_way_corridor(&[(5, Cardinal::East), (6, Cardinal::North)], &[CLEARANCE]);
+    let (west, east) = (Vec2::new(2.5, 2.5), Vec2::new(9.5, 2.5));
+
+    assert!(navmeshes
+        .find_path(CLEARANCE, west, east, DEFAULT_QUERY, DEFAULT_PATH_MODE)
+        .is_some());
+    assert!(navmeshes
+        .find_path(CLEARANCE, east, west, DEFAULT_QUERY, DEFAULT_PATH_MODE)
+        .is_none());
+    assert!(navmeshes.line_of_sight(CLEARANCE, west, east));
+    assert!(!navmeshes.line_of_sight(CLEARANCE, east, west));
+}
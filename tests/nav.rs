@@ -0,0 +1,449 @@
+#![cfg(feature = "bevy")]
+
+use std::time::Duration;
+
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use seldom_map_nav::prelude::*;
+
+const MAP_SIZE: UVec2 = UVec2::new(16, 16);
+const TILE_SIZE: Vec2 = Vec2::ONE;
+const CLEARANCE: f32 = 0.25;
+const STEP: Duration = Duration::from_millis(100);
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, MapNavPlugin::<Transform>::default()))
+        .init_resource::<NavTimeOverride>();
+    app
+}
+
+fn spawn_map(app: &mut App, navability: impl Fn(UVec2) -> Navability) -> Entity {
+    app.world
+        .spawn(Navmeshes::generate(MAP_SIZE, TILE_SIZE, navability, [CLEARANCE]).unwrap())
+        .id()
+}
+
+fn spawn_at(app: &mut App, position: Vec2) -> Entity {
+    app.world
+        .spawn(Transform::from_translation(position.extend(0.)))
+        .id()
+}
+
+fn step(app: &mut App) {
+    app.world.resource_mut::<NavTimeOverride>().step(STEP);
+    app.update();
+}
+
+fn position(app: &App, entity: Entity) -> Vec2 {
+    app.world
+        .get::<Transform>(entity)
+        .unwrap()
+        .translation
+        .truncate()
+}
+
+#[test]
+fn despawned_target_stops_navigation() {
+    let mut app = app();
+    let map = spawn_map(&mut app, |_| Navability::Navable);
+    let target = spawn_at(&mut app, Vec2::new(14.5, 14.5));
+    let navigator = spawn_at(&mut app, Vec2::new(1.5, 1.5));
+    app.world.entity_mut(navigator).insert(NavBundle {
+        pathfind: Pathfind::new(
+            map,
+            CLEARANCE,
+            Some(STEP),
+            PathTarget::Dynamic(target),
+            DEFAULT_QUERY,
+            DEFAULT_PATH_MODE,
+        ),
+        nav: Nav::new(1.),
+    });
+
+    let mut failures = ManualEventReader::<NavFailed>::default();
+    for _ in 0..3 {
+        step(&mut app);
+    }
+    assert!(!app.world.get::<Nav>(navigator).unwrap().done);
+
+    app.world.despawn(target);
+    let mut missing = 0;
+    for frame in 0..8 {
+        // Map changes don't restart navigation either
+        if frame >= 5 {
+            app.world
+                .get_mut::<Navmeshes>(map)
+                .unwrap()
+                .set_region_navability(Vec2::new(frame as f32, 8.5), 0.5, false)
+                .unwrap();
+        }
+        step(&mut app);
+        missing += failures
+            .iter(app.world.resource::<Events<NavFailed>>())
+            .filter(|failure| {
+                failure.entity == navigator && failure.reason == NavFailReason::MissingTarget
+            })
+            .count();
+    }
+
+    assert_eq!(missing, 1);
+    assert!(app
+        .world
+        .get::<Pathfind>(navigator)
+        .unwrap()
+        .path
+        .is_empty());
+    assert!(app.world.get::<Nav>(navigator).unwrap().done);
+
+    let stopped_at = position(&app, navigator);
+    step(&mut app);
+    assert_eq!(position(&app, navigator), stopped_at);
+
+    app.world
+        .get_mut::<Pathfind>(navigator)
+        .unwrap()
+        .set_target(PathTarget::Static(Vec2::new(1.5, 14.5)));
+    step(&mut app);
+    assert!(!app
+        .world
+        .get::<Pathfind>(navigator)
+        .unwrap()
+        .path
+        .is_empty());
+    assert!(!app.world.get::<Nav>(navigator).unwrap().done);
+}
+
+#[test]
+fn repathing_keeps_moving_forward() {
+    let mut app = app();
+    // A wall to walk alongside
+    let map = spawn_map(&mut app, |pos| match pos.x == 8 && pos.y < 12 {
+        true => Navability::Solid,
+        false => Navability::Navable,
+    });
+    let goal = Vec2::new(7.5, 14.5);
+    let navigator = spawn_at(&mut app, Vec2::new(7.5, 2.5));
+    app.world.entity_mut(navigator).insert(NavBundle {
+        pathfind: Pathfind::new(
+            map,
+            CLEARANCE,
+            Some(STEP),
+            PathTarget::Static(goal),
+            DEFAULT_QUERY,
+            DEFAULT_PATH_MODE,
+        ),
+        nav: Nav::new(0.4),
+    });
+
+    let mut previous = position(&app, navigator);
+    for _ in 0..500 {
+        // Something else, such as physics, keeps pushing the navigator slightly into the wall,
+        // off of the navmesh, further than it moves each step
+        if previous.y < 11. {
+            app.world
+                .get_mut::<Transform>(navigator)
+                .unwrap()
+                .translation
+                .x = 7.8;
+        }
+        step(&mut app);
+
+        let current = position(&app, navigator);
+        if app.world.get::<Nav>(navigator).unwrap().done {
+            break;
+        }
+
+        assert!(
+            current.y > previous.y,
+            "navigator stopped moving forward at {current}"
+        );
+        previous = current;
+    }
+
+    assert!(app.world.get::<Nav>(navigator).unwrap().done);
+    assert!(position(&app, navigator).distance(goal) < 0.01);
+}
+
+#[test]
+fn off_mesh_start_gets_path() {
+    let mut app = app();
+    let map = spawn_map(&mut app, |pos| match pos.x == 8 {
+        true => Navability::Solid,
+        false => Navability::Navable,
+    });
+    // Slightly inside the wall's clearance, such as after being pushed by physics
+    let start = Vec2::new(7.8, 5.);
+    assert!(!app
+        .world
+        .get::<Navmeshes>(map)
+        .unwrap()
+        .is_navigable(CLEARANCE, start));
+
+    let navigator = spawn_at(&mut app, start);
+    app.world.entity_mut(navigator).insert(Pathfind::new(
+        map,
+        CLEARANCE,
+        None,
+        PathTarget::Static(Vec2::new(2.5, 12.5)),
+        DEFAULT_QUERY,
+        DEFAULT_PATH_MODE,
+    ));
+    step(&mut app);
+
+    let pathfind = app.world.get::<Pathfind>(navigator).unwrap();
+    assert!(pathfind.snap_start);
+    assert!(!pathfind.path.is_empty());
+    assert_eq!(pathfind.path_status, Some(PathStatus::Complete));
+}
+
+#[test]
+fn pathfind_without_nav() {
+    let mut app = app();
+    let map = spawn_map(&mut app, |_| Navability::Navable);
+    let goal = Vec2::new(12.5, 9.5);
+    let navigator = spawn_at(&mut app, Vec2::new(1.5, 1.5));
+    app.world.entity_mut(navigator).insert(Pathfind::new(
+        map,
+        CLEARANCE,
+        None,
+        PathTarget::Static(goal),
+        DEFAULT_QUERY,
+        DEFAULT_PATH_MODE,
+    ));
+    assert!(!app.world.get::<Pathfind>(navigator).unwrap().done());
+
+    step(&mut app);
+    assert!(!app.world.get::<Pathfind>(navigator).unwrap().done());
+    // Nothing moves the entity without a `Nav`
+    assert_eq!(position(&app, navigator), Vec2::new(1.5, 1.5));
+
+    // Move the entity along the path with our own movement system
+    for _ in 0..16 {
+        let mut pathfind = app.world.get_mut::<Pathfind>(navigator).unwrap();
+        let Some(waypoint) = pathfind.path.pop_front() else {
+            break;
+        };
+        app.world
+            .get_mut::<Transform>(navigator)
+            .unwrap()
+            .translation = waypoint.extend(0.);
+        step(&mut app);
+    }
+
+    assert!(app.world.get::<Pathfind>(navigator).unwrap().done());
+    assert_eq!(position(&app, navigator), goal);
+}
+
+// A position that isn't a `Transform`, such as one synced over the network
+#[derive(Component)]
+struct NetPosition(Vec2);
+
+impl Position2 for NetPosition {
+    type Position = Vec2;
+
+    fn get(&self) -> Vec2 {
+        self.0
+    }
+
+    fn set(&mut self, pos: Vec2) {
+        self.0 = pos;
+    }
+}
+
+#[test]
+fn custom_position() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, MapNavPlugin::<NetPosition>::default()))
+        .init_resource::<NavTimeOverride>();
+    let map = spawn_map(&mut app, |pos| match pos.x == 8 && pos.y < 12 {
+        true => Navability::Solid,
+        false => Navability::Navable,
+    });
+    let target = app.world.spawn(NetPosition(Vec2::new(13.5, 2.5))).id();
+    let navigator = app
+        .world
+        .spawn((
+            NetPosition(Vec2::new(2.5, 2.5)),
+            NavBundle {
+                pathfind: Pathfind::new(
+                    map,
+                    CLEARANCE,
+                    None,
+                    PathTarget::Dynamic(target),
+                    DEFAULT_QUERY,
+                    DEFAULT_PATH_MODE,
+                ),
+                nav: Nav::new(10.),
+            },
+        ))
+        .id();
+
+    for _ in 0..100 {
+        step(&mut app);
+    }
+
+    assert!(app.world.get::<Nav>(navigator).unwrap().done);
+    assert_eq!(
+        app.world.get::<NetPosition>(navigator).unwrap().0,
+        Vec2::new(13.5, 2.5)
+    );
+}
+
+#[test]
+fn pausing_freezes_navigation() {
+    let mut app = app();
+    let map = spawn_map(&mut app, |_| Navability::Navable);
+    let navigator = spawn_at(&mut app, Vec2::new(1.5, 8.5));
+    app.world.entity_mut(navigator).insert((
+        NavBundle {
+            pathfind: Pathfind::new(
+                map,
+                CLEARANCE,
+                None,
+                PathTarget::Static(Vec2::new(14.5, 8.5)),
+                DEFAULT_QUERY,
+                DEFAULT_PATH_MODE,
+            ),
+            nav: Nav::new(1.),
+        },
+        NavTrail::new(100),
+        NavMotionState::default(),
+    ));
+    for _ in 0..3 {
+        step(&mut app);
+    }
+    assert_eq!(app.world.get::<Pathfind>(navigator).unwrap().path.len(), 1);
+
+    app.insert_resource(NavEnabled(false));
+    let paused_at = position(&app, navigator);
+    let trail_len = app.world.get::<NavTrail>(navigator).unwrap().points.len();
+    let motion_state = *app.world.get::<NavMotionState>(navigator).unwrap();
+    for frame in 0..10 {
+        // A wall goes up across the path during the pause
+        if frame == 5 {
+            app.world
+                .get_mut::<Navmeshes>(map)
+                .unwrap()
+                .set_region_navability(Vec2::new(8.5, 8.5), 3., false)
+                .unwrap();
+        }
+        step(&mut app);
+    }
+
+    assert_eq!(position(&app, navigator), paused_at);
+    assert_eq!(
+        app.world.get::<NavTrail>(navigator).unwrap().points.len(),
+        trail_len
+    );
+    assert_eq!(
+        *app.world.get::<NavMotionState>(navigator).unwrap(),
+        motion_state
+    );
+    assert_eq!(app.world.get::<Pathfind>(navigator).unwrap().path.len(), 1);
+
+    app.insert_resource(NavEnabled(true));
+    step(&mut app);
+    assert_ne!(position(&app, navigator), paused_at);
+    // The path was regenerated around the wall
+    assert!(app.world.get::<Pathfind>(navigator).unwrap().path.len() > 1);
+}
+
+#[cfg(feature = "diagnostics")]
+#[test]
+fn repath_diagnostics_use_nav_time() {
+    use bevy::diagnostic::DiagnosticsStore;
+    use seldom_map_nav::diagnostics::MapNavDiagnostics;
+
+    let mut app = app();
+    let map = spawn_map(&mut app, |_| Navability::Navable);
+    let navigator = spawn_at(&mut app, Vec2::new(1.5, 1.5));
+    app.world.entity_mut(navigator).insert(Pathfind::new(
+        map,
+        CLEARANCE,
+        Some(STEP),
+        PathTarget::Static(Vec2::new(14.5, 14.5)),
+        DEFAULT_QUERY,
+        DEFAULT_PATH_MODE,
+    ));
+    for _ in 0..5 {
+        step(&mut app);
+    }
+
+    // One repath every step of 100ms, however long the frames actually took
+    let repaths = app
+        .world
+        .resource::<DiagnosticsStore>()
+        .get(MapNavDiagnostics::REPATHS)
+        .unwrap()
+        .value()
+        .unwrap();
+    assert!(
+        (repaths - 10.).abs() < 0.001,
+        "{repaths} repaths per second"
+    );
+}
+
+#[test]
+fn async_region_changes_accumulate() {
+    let mut app = app();
+    let map = spawn_map(&mut app, |_| Navability::Navable);
+    step(&mut app);
+
+    let mut navmeshes = app.world.get_mut::<Navmeshes>(map).unwrap();
+    // The second change comes before the first one's task finishes, and replaces it
+    let _ = navmeshes.set_region_navability_async(Vec2::new(4.5, 4.5), 0.5, false);
+    let task = navmeshes.set_region_navability_async(Vec2::new(11.5, 11.5), 0.5, false);
+    app.world.entity_mut(map).insert(task);
+
+    let mut readies = ManualEventReader::<NavmeshReady>::default();
+    let mut ready = false;
+    for _ in 0..1000 {
+        step(&mut app);
+        if readies
+            .iter(app.world.resource::<Events<NavmeshReady>>())
+            .any(|ready| ready.map == map)
+        {
+            ready = true;
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert!(ready);
+
+    let navmeshes = app.world.get::<Navmeshes>(map).unwrap();
+    assert!(!navmeshes.is_navigable(CLEARANCE, Vec2::new(4.5, 4.5)));
+    assert!(!navmeshes.is_navigable(CLEARANCE, Vec2::new(11.5, 11.5)));
+    assert!(navmeshes.is_navigable(CLEARANCE, Vec2::new(8.5, 8.5)));
+}
+
+#[test]
+fn head_on_spacing_doesnt_deadlock() {
+    let mut app = app();
+    let map = spawn_map(&mut app, |_| Navability::Navable);
+    // Each starts within the other's spacing, heading toward the other's start
+    let (west, east) = (Vec2::new(7.5, 8.5), Vec2::new(8.5, 8.5));
+    let navigators = [(west, east), (east, west)].map(|(start, goal)| {
+        let navigator = spawn_at(&mut app, start);
+        app.world.entity_mut(navigator).insert(NavBundle {
+            pathfind: Pathfind::new(
+                map,
+                CLEARANCE,
+                None,
+                PathTarget::Static(goal),
+                DEFAULT_QUERY,
+                DEFAULT_PATH_MODE,
+            ),
+            nav: Nav::new(1.).with_spacing(2.),
+        });
+        (navigator, goal)
+    });
+
+    for _ in 0..100 {
+        step(&mut app);
+    }
+
+    for (navigator, goal) in navigators {
+        assert!(app.world.get::<Nav>(navigator).unwrap().done);
+        assert_eq!(position(&app, navigator), goal);
+    }
+}